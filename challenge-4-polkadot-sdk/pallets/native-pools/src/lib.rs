@@ -9,22 +9,51 @@
 use frame::prelude::*;
 use polkadot_sdk::polkadot_sdk_frame as frame;
 use polkadot_sdk::frame_support::{
-	traits::{Currency, ExistenceRequirement, Get},
+	traits::{
+		fungible::{Inspect, InspectFreeze, Mutate, MutateHold},
+		fungibles,
+		tokens::{
+			nonfungibles_v2::{self, Inspect as _, InspectEnumerable as _, Transfer as _},
+			Fortitude, Precision, Preservation,
+		},
+		Get,
+	},
 	PalletId,
 };
 use polkadot_sdk::sp_runtime::{
-	traits::{AccountIdConversion, Saturating, Zero, SaturatedConversion}
+	traits::{AccountIdConversion, AtLeast32BitUnsigned, Saturating, Zero, SaturatedConversion},
+	FixedPointNumber, FixedU128, Perbill,
+};
+use polkadot_sdk::frame_support::{ensure, storage::with_storage_layer};
+use polkadot_sdk::frame_system::offchain::{
+	AppCrypto, CreateSignedTransaction, SendSignedTransaction, Signer,
+};
+use polkadot_sdk::sp_runtime::{
+	offchain::storage::{MutateStorageError, StorageRetrievalError, StorageValueRef},
+	KeyTypeId,
 };
-use polkadot_sdk::frame_support::ensure;
 
 // Re-export all pallet parts, this is needed to properly import the pallet into the runtime.
 pub use pallet::*;
 
+pub mod migrations;
+mod mock;
+pub mod runtime_api;
+mod tests;
+
+/// The in-code storage version.
+const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
 type BalanceOf<T> =
-	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	<<T as Config>::Currency as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
+type FreezeIdOf<T> =
+	<<T as Config>::Currency as InspectFreeze<<T as frame_system::Config>::AccountId>>::Id;
 
 type BlockNumberFor<T> = frame_system::pallet_prelude::BlockNumberFor<T>;
 
+/// Identifier of a deposit position, the NFT item representing it
+pub type PositionId = u32;
+
 /// Information about a user's deposit in the pool
 #[derive(Clone, Encode, Decode, MaxEncodedLen, TypeInfo, Debug, PartialEq)]
 pub struct DepositInfo<Balance, BlockNumber> {
@@ -34,6 +63,356 @@ pub struct DepositInfo<Balance, BlockNumber> {
 	pub deposit_block: BlockNumber,
 	/// The reward per share at the time of deposit (used for reward calculation)
 	pub reward_debt: Balance,
+	/// The reward weight of the deposit, its amount scaled by the lock term multipliers
+	pub shares: Balance,
+	/// The block from which the deposit can be withdrawn
+	pub unlock_block: BlockNumber,
+}
+
+/// A deposit in the compounding vault
+#[derive(Clone, Encode, Decode, MaxEncodedLen, TypeInfo, Debug, PartialEq, Eq)]
+pub struct CompoundDeposit<Balance, BlockNumber> {
+	/// The vault shares owned by the depositor
+	pub shares: Balance,
+	/// The block number of the latest deposit, used for the exit fee
+	pub deposit_block: BlockNumber,
+}
+
+/// The reward accounting of a pool, the native pool or a token pool
+///
+/// Rewards are shared out over the pool shares through the accumulated reward per share, each
+/// deposit keeping the reward debt its shares were settled at.
+#[derive(Clone, Encode, Decode, MaxEncodedLen, TypeInfo, Debug, PartialEq, Eq, Default)]
+pub struct PoolState<Balance> {
+	/// Total amount of tokens deposited in the pool
+	pub total_deposited: Balance,
+	/// Total reward weight of the deposits in the pool
+	pub total_shares: Balance,
+	/// Native rewards not paid out yet
+	pub total_rewards: Balance,
+	/// Accumulated reward per share (scaled by 1e12 for precision)
+	pub acc_reward_per_share: Balance,
+}
+
+impl<Balance: AtLeast32BitUnsigned + Copy> PoolState<Balance> {
+	/// Precision factor for reward calculations (1e12)
+	pub fn precision() -> Balance {
+		(1_000_000_000_000u128).saturated_into()
+	}
+
+	/// The rewards accrued by `shares` at the accumulated reward per share `acc_per_share`
+	pub fn accrued(acc_per_share: Balance, shares: Balance) -> Balance {
+		acc_per_share.saturating_mul(shares) / Self::precision()
+	}
+
+	/// The increase of the accumulated reward per share from sharing out `amount` of rewards
+	/// over `total_shares`
+	pub fn acc_increment(amount: Balance, total_shares: Balance) -> Balance {
+		amount.saturating_mul(Self::precision()) / total_shares
+	}
+
+	/// The pending rewards of the deposit `info`
+	pub fn pending<BlockNumber>(&self, info: &DepositInfo<Balance, BlockNumber>) -> Balance {
+		Self::accrued(self.acc_reward_per_share, info.shares).saturating_sub(info.reward_debt)
+	}
+
+	/// Take the pending rewards of `info` out of the pool, returning them to be paid out
+	pub fn settle<BlockNumber>(&mut self, info: &mut DepositInfo<Balance, BlockNumber>) -> Balance {
+		let pending = self.pending(info);
+		info.reward_debt = Self::accrued(self.acc_reward_per_share, info.shares);
+		self.total_rewards = self.total_rewards.saturating_sub(pending);
+		pending
+	}
+
+	/// Add `amount` of tokens with a reward weight of `shares` to `info`, settling its pending
+	/// rewards first
+	pub fn stake<BlockNumber>(
+		&mut self,
+		info: &mut DepositInfo<Balance, BlockNumber>,
+		amount: Balance,
+		shares: Balance,
+	) -> Balance {
+		let pending = self.settle(info);
+		info.amount = info.amount.saturating_add(amount);
+		info.shares = info.shares.saturating_add(shares);
+		info.reward_debt = Self::accrued(self.acc_reward_per_share, info.shares);
+		self.total_deposited = self.total_deposited.saturating_add(amount);
+		self.total_shares = self.total_shares.saturating_add(shares);
+		pending
+	}
+
+	/// Remove `amount` of tokens from `info` along with the same part of its shares, settling
+	/// its pending rewards first
+	pub fn unstake<BlockNumber>(
+		&mut self,
+		info: &mut DepositInfo<Balance, BlockNumber>,
+		amount: Balance,
+	) -> Balance {
+		let pending = self.settle(info);
+		let removed_shares = if amount >= info.amount {
+			info.shares
+		} else {
+			info.shares.saturating_mul(amount) / info.amount
+		};
+		info.amount = info.amount.saturating_sub(amount);
+		info.shares = info.shares.saturating_sub(removed_shares);
+		info.reward_debt = Self::accrued(self.acc_reward_per_share, info.shares);
+		self.total_deposited = self.total_deposited.saturating_sub(amount);
+		self.total_shares = self.total_shares.saturating_sub(removed_shares);
+		pending
+	}
+
+	/// Change the reward weight of `info` to `shares`, settling its pending rewards first
+	pub fn reweight<BlockNumber>(
+		&mut self,
+		info: &mut DepositInfo<Balance, BlockNumber>,
+		shares: Balance,
+	) -> Balance {
+		let pending = self.settle(info);
+		self.total_shares = self.total_shares.saturating_sub(info.shares).saturating_add(shares);
+		info.shares = shares;
+		info.reward_debt = Self::accrued(self.acc_reward_per_share, info.shares);
+		pending
+	}
+
+	/// Remove `amount` of tokens from `info` along with the same part of its shares, without
+	/// paying out the pending rewards of the removed part, which stay in the pool
+	pub fn forfeit<BlockNumber>(
+		&mut self,
+		info: &mut DepositInfo<Balance, BlockNumber>,
+		amount: Balance,
+	) {
+		let (removed_shares, removed_debt) = if amount >= info.amount {
+			(info.shares, info.reward_debt)
+		} else {
+			(
+				info.shares.saturating_mul(amount) / info.amount,
+				info.reward_debt.saturating_mul(amount) / info.amount,
+			)
+		};
+		info.amount = info.amount.saturating_sub(amount);
+		info.shares = info.shares.saturating_sub(removed_shares);
+		info.reward_debt = info.reward_debt.saturating_sub(removed_debt);
+		self.total_deposited = self.total_deposited.saturating_sub(amount);
+		self.total_shares = self.total_shares.saturating_sub(removed_shares);
+	}
+
+	/// Add `amount` of rewards to the pool, sharing them out over the current shares
+	///
+	/// Without shares, the rewards are kept in the pool without accruing to anyone.
+	pub fn distribute(&mut self, amount: Balance) {
+		self.total_rewards = self.total_rewards.saturating_add(amount);
+		if !self.total_shares.is_zero() {
+			self.acc_reward_per_share = self
+				.acc_reward_per_share
+				.saturating_add(Self::acc_increment(amount, self.total_shares));
+		}
+	}
+}
+
+/// Limits on the deposits into a token pool
+#[derive(
+	Clone, Encode, Decode, MaxEncodedLen, TypeInfo, Debug, PartialEq, Eq, Default, DecodeWithMemTracking,
+)]
+pub struct DepositLimits<Balance> {
+	/// The minimum amount of a single deposit
+	pub min_deposit: Balance,
+	/// The maximum an account can have deposited (None for no limit)
+	pub max_per_account: Option<Balance>,
+	/// The maximum total deposited (None for no limit)
+	pub pool_cap: Option<Balance>,
+}
+
+/// The rewards shared out over the native pool in a day, and its state after the latest ones
+#[derive(Clone, Encode, Decode, MaxEncodedLen, TypeInfo, Debug, PartialEq, Eq)]
+pub struct RewardSnapshot<Balance, BlockNumber> {
+	/// The block the latest rewards of the day were shared out in
+	pub block: BlockNumber,
+	/// The amount of rewards shared out in the day
+	pub rewards: Balance,
+	/// Total amount of tokens deposited in the pool
+	pub total_deposited: Balance,
+	/// Accumulated reward per share after the latest rewards (scaled by 1e12 for precision)
+	pub acc_reward_per_share: Balance,
+}
+
+/// A holder of pool shares
+///
+/// Only regular deposits and positions earn reward assets.
+#[derive(Clone, Encode, Decode, MaxEncodedLen, TypeInfo, Debug, PartialEq, Eq)]
+pub enum Depositor<AccountId, StakingToken> {
+	/// The regular deposit of an account
+	Account(AccountId),
+	/// A deposit position
+	Position(PositionId),
+	/// The deposit of an account in the token pool of a staking token
+	Token(StakingToken, AccountId),
+	/// The compounding vault deposit of an account
+	Compounding(AccountId),
+}
+
+/// The depositor type of a runtime
+pub type DepositorOf<T> =
+	Depositor<<T as frame_system::Config>::AccountId, <T as Config>::StakingToken>;
+
+/// Where the native rewards of a regular deposit are paid
+#[derive(
+	Clone, Encode, Decode, MaxEncodedLen, TypeInfo, Debug, PartialEq, Eq, Default, DecodeWithMemTracking,
+)]
+pub enum RewardDestination<AccountId> {
+	/// Paid to the depositor
+	#[default]
+	Stay,
+	/// Paid to another account
+	Account(AccountId),
+	/// Added to the deposit
+	Compound,
+}
+
+/// A lock term depositors can commit to in exchange for a higher reward weight
+#[derive(Clone, Copy, Encode, Decode, MaxEncodedLen, TypeInfo, Debug, PartialEq, Eq)]
+pub struct LockTerm<BlockNumber> {
+	/// Number of blocks the deposit stays locked
+	pub duration: BlockNumber,
+	/// Reward weight of the deposit in percent, 100 being the weight of an unlocked deposit
+	pub reward_multiplier: u32,
+}
+
+/// Withdrawn principal waiting for the unbonding period to pass
+#[derive(Clone, Encode, Decode, MaxEncodedLen, TypeInfo, Debug, PartialEq, Eq)]
+pub struct UnlockChunk<Balance, BlockNumber> {
+	/// The amount being unbonded
+	pub value: Balance,
+	/// The block from which the amount can be withdrawn
+	pub unlock_block: BlockNumber,
+}
+
+/// A fee on withdrawn principal that declines linearly with the age of the deposit
+#[derive(Clone, Copy, Encode, Decode, MaxEncodedLen, TypeInfo, Debug, PartialEq, Eq, DecodeWithMemTracking)]
+pub struct ExitFee<BlockNumber> {
+	/// The fee on a deposit withdrawn in the block it was made
+	pub initial: Perbill,
+	/// Number of blocks after which withdrawals are free
+	pub decay_period: BlockNumber,
+	/// Part of the fee sent to the treasury, the rest goes to the remaining depositors
+	pub treasury_share: Perbill,
+}
+
+impl<BlockNumber: Zero> Default for LockTerm<BlockNumber> {
+	fn default() -> Self {
+		LockTerm { duration: Zero::zero(), reward_multiplier: 100 }
+	}
+}
+
+/// The curve over which rewards vest when paid out as a vesting schedule
+#[derive(Clone, Copy, Encode, Decode, MaxEncodedLen, TypeInfo, Debug, PartialEq, Eq, DecodeWithMemTracking)]
+pub struct VestingCurve<BlockNumber> {
+	/// Number of blocks in each vesting period
+	pub period: BlockNumber,
+	/// Number of periods until the reward is fully vested
+	pub period_count: u32,
+}
+
+/// Pays pool rewards out as a vesting schedule
+pub trait RewardVesting<AccountId, Balance, BlockNumber> {
+	/// Transfer `amount` from `source` to `dest`, vesting over `period_count` periods of
+	/// `period` blocks
+	fn vest_reward(
+		source: &AccountId,
+		dest: &AccountId,
+		amount: Balance,
+		period: BlockNumber,
+		period_count: u32,
+	) -> DispatchResult;
+}
+
+impl<AccountId, Balance, BlockNumber> RewardVesting<AccountId, Balance, BlockNumber> for () {
+	fn vest_reward(
+		_source: &AccountId,
+		_dest: &AccountId,
+		_amount: Balance,
+		_period: BlockNumber,
+		_period_count: u32,
+	) -> DispatchResult {
+		Err(DispatchError::Other("Reward vesting is not supported"))
+	}
+}
+
+/// Reacts to changes of the native pool and the token pools, such as to award loyalty points
+///
+/// Every method defaults to doing nothing. Handlers are called after the change was applied.
+/// Native pool deposits and withdrawals cover regular deposits, positions and the compounding
+/// vault, so that they add up to [`PoolInspect::stake_of`].
+pub trait OnPoolChange<AccountId, Balance, StakingToken> {
+	/// `who` deposited `amount` into the pool
+	fn on_deposit(_who: &AccountId, _amount: Balance) {}
+
+	/// `who` withdrew `amount` of its deposit from the pool
+	fn on_withdraw(_who: &AccountId, _amount: Balance) {}
+
+	/// `amount` of native rewards were paid out for the deposit of `who`
+	fn on_claim(_who: &AccountId, _amount: Balance) {}
+
+	/// `amount` of native rewards were deposited into the pool
+	fn on_rewards_deposited(_amount: Balance) {}
+
+	/// `who` deposited `amount` into the token pool of `token`
+	fn on_token_deposit(_token: &StakingToken, _who: &AccountId, _amount: Balance) {}
+
+	/// `who` withdrew `amount` of its deposit from the token pool of `token`
+	fn on_token_withdraw(_token: &StakingToken, _who: &AccountId, _amount: Balance) {}
+
+	/// `amount` of native rewards were paid out for the deposit of `who` in the token pool
+	/// of `token`
+	fn on_token_claim(_token: &StakingToken, _who: &AccountId, _amount: Balance) {}
+
+	/// `amount` of native rewards were deposited into the token pool of `token`
+	fn on_token_rewards_deposited(_token: &StakingToken, _amount: Balance) {}
+}
+
+impl<AccountId, Balance, StakingToken> OnPoolChange<AccountId, Balance, StakingToken> for () {}
+
+/// Read-only view of the native pool, for pallets that should not depend on this one
+pub trait PoolInspect<AccountId> {
+	/// The balance type of the pool
+	type Balance;
+
+	/// The amount `who` has staked, in its regular deposit, its positions and the compounding
+	/// vault
+	fn stake_of(who: &AccountId) -> Self::Balance;
+
+	/// The total amount staked in the pool
+	fn total_stake() -> Self::Balance;
+}
+
+/// Key type of the keys the keeper signs reward distributions with
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"npkp");
+
+/// Offchain storage key of the last day the keeper submitted a reward distribution for
+const KEEPER_LAST_DAY: &[u8] = b"native-pools::keeper::last-day";
+
+/// Keeper keys, sr25519 application keys of type [`KEY_TYPE`]
+pub mod crypto {
+	use super::KEY_TYPE;
+	use polkadot_sdk::{
+		frame_system::offchain::AppCrypto,
+		sp_core::sr25519::{Public as Sr25519Public, Signature as Sr25519Signature},
+		sp_runtime::{
+			app_crypto::{app_crypto, sr25519},
+			MultiSignature, MultiSigner,
+		},
+	};
+
+	app_crypto!(sr25519, KEY_TYPE);
+
+	/// Signs reward distributions for runtimes with `MultiSignature` accounts
+	pub struct KeeperAuthId;
+
+	impl AppCrypto<MultiSigner, MultiSignature> for KeeperAuthId {
+		type RuntimeAppPublic = Public;
+		type GenericPublic = Sr25519Public;
+		type GenericSignature = Sr25519Signature;
+	}
 }
 
 #[frame::pallet]
@@ -41,27 +420,179 @@ pub mod pallet {
 	use super::*;
 
 	#[pallet::config]
-	pub trait Config: polkadot_sdk::frame_system::Config {
+	pub trait Config:
+		polkadot_sdk::frame_system::Config + CreateSignedTransaction<Call<Self>>
+	{
+		/// The overarching hold reason
+		type RuntimeHoldReason: From<HoldReason>;
 
+		/// The native currency, held in the pool account or on hold for vesting deposits
+		type Currency: Inspect<Self::AccountId>
+			+ Mutate<Self::AccountId>
+			+ MutateHold<Self::AccountId, Reason = Self::RuntimeHoldReason>
+			+ InspectFreeze<Self::AccountId>;
 
-		type Currency: Currency<Self::AccountId>;
+		/// The freeze placed by the vesting pallet on tokens that have not vested yet
+		type VestingFreezeId: Get<FreezeIdOf<Self>>;
 
 		/// The pallet's account ID for holding pooled funds
 		#[pallet::constant]
 		type PalletId: Get<PalletId>;
 
-		/// The origin that can deposit rewards (team members)
-		type RewardOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		/// The origin that can deposit rewards (team members), resolving to the paying account
+		type RewardOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
+
+		/// Vests rewards when a reward vesting curve is set
+		type RewardVesting: RewardVesting<Self::AccountId, BalanceOf<Self>, BlockNumberFor<Self>>;
+
+		/// The origin that can change the pool parameters
+		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// The lock terms depositors can choose from
+		#[pallet::constant]
+		type LockTerms: Get<Vec<LockTerm<BlockNumberFor<Self>>>>;
+
+		/// Maximum number of lock term multipliers that can end at the same block
+		#[pallet::constant]
+		type MaxBoostExpiriesPerBlock: Get<u32>;
+
+		/// Maximum number of blocks lock term multipliers can be waiting to end at
+		#[pallet::constant]
+		type MaxBoostExpiryBlocks: Get<u32>;
+
+		/// Number of blocks withdrawn principal stays in the pool, zero to pay it out at once
+		#[pallet::constant]
+		type UnbondingPeriod: Get<BlockNumberFor<Self>>;
+
+		/// Maximum number of unlocking chunks an account can have
+		#[pallet::constant]
+		type MaxUnlockingChunks: Get<u32>;
+
+		/// The account receiving the treasury share of exit fees
+		type TreasuryAccount: Get<Self::AccountId>;
+
+		/// Identifier of the assets used by the pool
+		type AssetId: Member + Parameter + MaxEncodedLen;
+
+		/// The assets pallet holding the receipt token
+		type Assets: fungibles::Inspect<
+				Self::AccountId,
+				AssetId = Self::AssetId,
+				Balance = BalanceOf<Self>,
+			> + fungibles::Mutate<Self::AccountId>
+			+ fungibles::Create<Self::AccountId>;
+
+		/// The asset minted as a receipt for regular deposits, created at genesis
+		#[pallet::constant]
+		type ReceiptAssetId: Get<Self::AssetId>;
+
+		/// Identifier of the NFT collection of positions
+		type CollectionId: Member + Parameter + MaxEncodedLen + Copy;
+
+		/// The NFT pallet holding the positions
+		type Nfts: nonfungibles_v2::Inspect<
+				Self::AccountId,
+				CollectionId = Self::CollectionId,
+				ItemId = PositionId,
+			> + nonfungibles_v2::Create<Self::AccountId, Self::NftCollectionConfig>
+			+ nonfungibles_v2::Mutate<Self::AccountId, Self::NftItemConfig>
+			+ nonfungibles_v2::Transfer<Self::AccountId>
+			+ nonfungibles_v2::InspectEnumerable<Self::AccountId>;
+
+		/// Configuration of the position collection, created on first use
+		type NftCollectionConfig: Default;
+
+		/// Configuration of the position items
+		type NftItemConfig: Default;
+
+		/// Maximum number of assets rewards can be paid in next to the native currency
+		#[pallet::constant]
+		type MaxRewardAssets: Get<u32>;
+
+		/// The storage deposit taken for each new deposit entry
+		#[pallet::constant]
+		type EntryDeposit: Get<BalanceOf<Self>>;
+
+		/// Virtual shares and tokens added to the compounding vault when pricing its shares
+		///
+		/// They keep a donation to a nearly empty vault from inflating the share price, so that
+		/// later deposits cannot be rounded down to nothing to the benefit of the first one.
+		#[pallet::constant]
+		type VaultVirtualShares: Get<BalanceOf<Self>>;
+
+		/// Identifier of the tokens token pools can be opened for
+		type StakingToken: Member + Parameter + MaxEncodedLen;
+
+		/// The tokens token pools can be opened for
+		type StakingAssets: fungibles::Mutate<
+			Self::AccountId,
+			AssetId = Self::StakingToken,
+			Balance = BalanceOf<Self>,
+		>;
+
+		/// Number of reward snapshots kept, the oldest being overwritten first
+		#[pallet::constant]
+		type MaxRewardSnapshots: Get<u32>;
+
+		/// Number of blocks in a day, used to annualise the reward rate and to schedule the
+		/// daily reward distribution
+		#[pallet::constant]
+		type BlocksPerDay: Get<BlockNumberFor<Self>>;
+
+		/// The account scheduled rewards are paid from
+		type RewardReserve: Get<Self::AccountId>;
+
+		/// The keys the keeper offchain worker signs reward distributions with
+		type KeeperAuthorityId: AppCrypto<Self::Public, Self::Signature>;
+
+		/// Handler notified of deposits, withdrawals, claims and reward deposits
+		type OnPoolChange: OnPoolChange<Self::AccountId, BalanceOf<Self>, Self::StakingToken>;
 	}
 
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
+	#[pallet::genesis_config]
+	#[derive(DefaultNoBound)]
+	pub struct GenesisConfig<T: Config> {
+		/// The initial members of the reward team
+		pub reward_team: Vec<T::AccountId>,
+		#[serde(skip)]
+		pub _config: PhantomData<T>,
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+		fn build(&self) {
+			Pallet::<T>::create_receipt_asset().expect("receipt asset is created at genesis");
+			for who in &self.reward_team {
+				RewardTeam::<T>::insert(who, ());
+			}
+		}
+	}
+
+	/// A reason for the pallet placing a hold on funds.
+	#[pallet::composite_enum]
+	pub enum HoldReason {
+		/// Vesting tokens committed to the pool
+		#[codec(index = 0)]
+		LockedDeposit,
+		/// Storage deposit of a deposit entry
+		#[codec(index = 1)]
+		StorageDeposit,
+	}
+
 	/// Total amount deposited in the pool by all users
 	#[pallet::storage]
 	#[pallet::getter(fn total_deposited)]
 	pub type TotalDeposited<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
 
+	/// Total reward weight of all deposits in the pool
+	#[pallet::storage]
+	#[pallet::getter(fn total_shares)]
+	pub type TotalShares<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
 	/// Total rewards accumulated in the pool
 	#[pallet::storage]
 	#[pallet::getter(fn total_rewards)]
@@ -83,11 +614,219 @@ pub mod pallet {
 		OptionQuery,
 	>;
 
+	/// Part of each user's deposit made of vesting tokens kept on hold in their own account
+	#[pallet::storage]
+	#[pallet::getter(fn locked_deposits)]
+	pub type LockedDeposits<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+	/// Withdrawn principal of each user waiting for the unbonding period to pass
+	#[pallet::storage]
+	#[pallet::getter(fn unbonding)]
+	pub type Unbonding<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		BoundedVec<UnlockChunk<BalanceOf<T>, BlockNumberFor<T>>, T::MaxUnlockingChunks>,
+		ValueQuery,
+	>;
+
+	/// The NFT collection of positions, once created
+	#[pallet::storage]
+	#[pallet::getter(fn position_collection)]
+	pub type PositionCollection<T: Config> = StorageValue<_, T::CollectionId, OptionQuery>;
+
+	/// The identifier of the next position
+	#[pallet::storage]
+	#[pallet::getter(fn next_position_id)]
+	pub type NextPositionId<T: Config> = StorageValue<_, PositionId, ValueQuery>;
+
+	/// The deposit of each position
+	#[pallet::storage]
+	#[pallet::getter(fn positions)]
+	pub type Positions<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		PositionId,
+		DepositInfo<BalanceOf<T>, BlockNumberFor<T>>,
+		OptionQuery,
+	>;
+
+	/// Assets rewards can be paid in next to the native currency
+	#[pallet::storage]
+	#[pallet::getter(fn reward_assets)]
+	pub type RewardAssets<T: Config> =
+		StorageValue<_, BoundedVec<T::AssetId, T::MaxRewardAssets>, ValueQuery>;
+
+	/// Accumulated reward per share of each reward asset (scaled by 1e12 for precision)
+	#[pallet::storage]
+	#[pallet::getter(fn asset_acc_reward_per_share)]
+	pub type AssetAccRewardPerShare<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AssetId, BalanceOf<T>, ValueQuery>;
+
+	/// Reward debt of each depositor in each reward asset
+	#[pallet::storage]
+	pub type AssetRewardDebts<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		DepositorOf<T>,
+		Blake2_128Concat,
+		T::AssetId,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	/// Token pools by staking token
+	#[pallet::storage]
+	#[pallet::getter(fn token_pools)]
+	pub type TokenPools<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::StakingToken, PoolState<BalanceOf<T>>, OptionQuery>;
+
+	/// Limits on the deposits into each token pool, none if not set
+	#[pallet::storage]
+	#[pallet::getter(fn token_pool_limits)]
+	pub type TokenPoolLimits<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::StakingToken, DepositLimits<BalanceOf<T>>, ValueQuery>;
+
+	/// Deposits in token pools
+	#[pallet::storage]
+	#[pallet::getter(fn token_deposits)]
+	pub type TokenDeposits<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::StakingToken,
+		Blake2_128Concat,
+		T::AccountId,
+		DepositInfo<BalanceOf<T>, BlockNumberFor<T>>,
+		OptionQuery,
+	>;
+
+	/// Minimum amount of a single deposit into the native pool
+	#[pallet::storage]
+	#[pallet::getter(fn min_deposit)]
+	pub type MinDeposit<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+	/// Maximum amount an account can have deposited in the native pool, no limit if not set
+	#[pallet::storage]
+	#[pallet::getter(fn max_deposit_per_account)]
+	pub type MaxDepositPerAccount<T: Config> = StorageValue<_, BalanceOf<T>, OptionQuery>;
+
+	/// Maximum total deposited in the native pool, no limit if not set
+	#[pallet::storage]
+	#[pallet::getter(fn pool_cap)]
+	pub type PoolCap<T: Config> = StorageValue<_, BalanceOf<T>, OptionQuery>;
+
+	/// The account that paid the storage deposit of each deposit entry, and its amount
+	#[pallet::storage]
+	#[pallet::getter(fn entry_deposits)]
+	pub type EntryDeposits<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		DepositorOf<T>,
+		(T::AccountId, BalanceOf<T>),
+		OptionQuery,
+	>;
+
+	/// Where the native rewards of each regular deposit are paid, to the depositor if not set
+	#[pallet::storage]
+	#[pallet::getter(fn reward_destination)]
+	pub type RewardDestinations<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		RewardDestination<T::AccountId>,
+		ValueQuery,
+	>;
+
+	/// Whether the native pool and the token pools are paused
+	#[pallet::storage]
+	#[pallet::getter(fn paused)]
+	pub type Paused<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// Deposits in the compounding vault
+	#[pallet::storage]
+	#[pallet::getter(fn compound_deposits)]
+	pub type CompoundDeposits<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		CompoundDeposit<BalanceOf<T>, BlockNumberFor<T>>,
+		OptionQuery,
+	>;
+
+	/// Total shares of the compounding vault
+	#[pallet::storage]
+	#[pallet::getter(fn total_compound_shares)]
+	pub type TotalCompoundShares<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+	/// Ring buffer of daily snapshots of the rewards shared out over the native pool
+	#[pallet::storage]
+	#[pallet::getter(fn reward_snapshots)]
+	pub type RewardSnapshots<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		u32,
+		RewardSnapshot<BalanceOf<T>, BlockNumberFor<T>>,
+		OptionQuery,
+	>;
+
+	/// Index in `RewardSnapshots` the next snapshot is written to
+	#[pallet::storage]
+	#[pallet::getter(fn next_reward_snapshot)]
+	pub type NextRewardSnapshot<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// Amount moved from the reward reserve into the native pool once a day, no rewards are
+	/// scheduled if not set
+	#[pallet::storage]
+	#[pallet::getter(fn scheduled_reward)]
+	pub type ScheduledReward<T: Config> = StorageValue<_, BalanceOf<T>, OptionQuery>;
+
+	/// Accounts accepted by [`EnsureRewardTeam`], such as team members and keepers
+	#[pallet::storage]
+	pub type RewardTeam<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, (), OptionQuery>;
+
+	/// The last day scheduled rewards were distributed for
+	#[pallet::storage]
+	#[pallet::getter(fn last_reward_day)]
+	pub type LastRewardDay<T: Config> = StorageValue<_, u32, OptionQuery>;
+
+	/// Fee charged on withdrawals, no fee if not set
+	#[pallet::storage]
+	#[pallet::getter(fn exit_fee_schedule)]
+	pub type ExitFeeSchedule<T: Config> = StorageValue<_, ExitFee<BlockNumberFor<T>>, OptionQuery>;
+
 	/// Last block when rewards were updated
 	#[pallet::storage]
 	#[pallet::getter(fn last_reward_block)]
 	pub type LastRewardBlock<T: Config> = StorageValue<_, BlockNumberFor<T>, ValueQuery>;
 
+	/// Curve over which rewards vest, rewards are paid out liquid if not set
+	#[pallet::storage]
+	#[pallet::getter(fn reward_vesting_curve)]
+	pub type RewardVestingCurve<T: Config> =
+		StorageValue<_, VestingCurve<BlockNumberFor<T>>, OptionQuery>;
+
+	/// Regular deposits and positions whose lock term multiplier ends at each block
+	#[pallet::storage]
+	pub type BoostExpiries<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		BlockNumberFor<T>,
+		BoundedVec<DepositorOf<T>, T::MaxBoostExpiriesPerBlock>,
+		ValueQuery,
+	>;
+
+	/// Blocks with entries in `BoostExpiries`, earliest first
+	#[pallet::storage]
+	pub type BoostExpiryBlocks<T: Config> =
+		StorageValue<_, BoundedVec<BlockNumberFor<T>, T::MaxBoostExpiryBlocks>, ValueQuery>;
+
+	/// Native rewards of each user that could not be vested when paid, kept in the pool
+	/// account until vested with `vest_held_rewards`
+	#[pallet::storage]
+	#[pallet::getter(fn held_rewards)]
+	pub type HeldRewards<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
 
 	#[pallet::error]
 	pub enum Error<T> {
@@ -101,6 +840,89 @@ pub mod pallet {
 		InsufficientPoolBalance,
 		/// Arithmetic overflow occurred
 		ArithmeticOverflow,
+		/// Not enough vesting tokens to commit to the pool
+		InsufficientLockedBalance,
+		/// Vesting curve has a zero period or period count
+		InvalidVestingCurve,
+		/// No lock term with the given index
+		UnknownLockTerm,
+		/// Deposit is still locked
+		DepositLocked,
+		/// Too many withdrawals are still unbonding
+		TooManyUnlockingChunks,
+		/// No unbonded funds to withdraw
+		NoUnbondedFunds,
+		/// Not enough funds unbonding to rebond
+		InsufficientUnbonding,
+		/// Exit fee has a zero decay period
+		InvalidExitFee,
+		/// Not enough receipt tokens
+		InsufficientReceipts,
+		/// Receipt tokens cannot be transferred to their owner
+		SelfTransfer,
+		/// Receipt tokens cannot be transferred to the compounding vault
+		VaultTransfer,
+		/// No position with the given identifier
+		UnknownPosition,
+		/// Caller does not own the position
+		NotPositionOwner,
+		/// Asset is not a reward asset
+		UnknownRewardAsset,
+		/// Asset already is a reward asset
+		RewardAssetExists,
+		/// Too many reward assets
+		TooManyRewardAssets,
+		/// Pool has no depositors to reward
+		NoDepositors,
+		/// No token pool for the given token
+		UnknownTokenPool,
+		/// A token pool for the given token already exists
+		TokenPoolExists,
+		/// Deposit is below the minimum deposit
+		DepositTooSmall,
+		/// Deposit would exceed the limit per account
+		AccountLimitExceeded,
+		/// Deposit would exceed the pool cap
+		PoolCapExceeded,
+		/// Pool is paused
+		Paused,
+		/// Pool is not paused
+		NotPaused,
+		/// No reward is scheduled
+		NoScheduledReward,
+		/// Rewards can only be distributed for the current day
+		InvalidRewardDay,
+		/// Rewards were already distributed for the day
+		RewardAlreadyDistributed,
+		/// User has no held rewards
+		NoHeldRewards,
+		/// Too many lock term multipliers end around the same block
+		TooManyBoostExpiries,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// End the lock term multipliers of the deposits, positions and token pool deposits
+		/// unlocking by `now`
+		///
+		/// While the pool is paused no rewards accrue, so the multipliers ending meanwhile are
+		/// ended once it is unpaused.
+		fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+			if Self::paused() {
+				return T::DbWeight::get().reads(1);
+			}
+			Self::expire_boosts(now)
+		}
+
+		/// Submit the scheduled reward distribution of the day, signed with a keeper key
+		///
+		/// To run a keeper, start the node with `--offchain-worker always` and insert a
+		/// [`KEY_TYPE`] key of an account accepted by `RewardOrigin` with `author_insertKey`.
+		fn offchain_worker(now: BlockNumberFor<T>) {
+			if let Err(e) = Self::run_keeper(now) {
+				log::debug!(target: "runtime::native-pools", "keeper: {}", e);
+			}
+		}
 	}
 
 	#[pallet::call]
@@ -116,53 +938,31 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			amount: BalanceOf<T>,
 		) -> DispatchResult {
-			let who = frame_system::Pallet::<T>::ensure_signed(origin)?;
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused()?;
 			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
 
-			let free_balance = T::Currency::free_balance(&who);
+			let free_balance =
+				T::Currency::reducible_balance(&who, Preservation::Expendable, Fortitude::Polite);
 			ensure!(free_balance >= amount, Error::<T>::InsufficientBalance);
+			Self::ensure_deposit_allowed(Self::deposited_by(&who), amount)?;
 
 			Self::update_pool()?;
 
 			let pool_account = Self::account_id();
-			T::Currency::transfer(&who, &pool_account, amount, ExistenceRequirement::AllowDeath)?;
-
-			let pending = Self::calculate_pending_rewards(&who).unwrap_or_default();
-			if !pending.is_zero() {
-				let pool_balance = T::Currency::free_balance(&pool_account);
-				ensure!(pool_balance >= pending, Error::<T>::InsufficientPoolBalance);
-				T::Currency::transfer(&pool_account, &who, pending, ExistenceRequirement::AllowDeath)?;
-				TotalRewards::<T>::mutate(|r| *r = r.saturating_sub(pending));
-			}
-
-			let acc_per_share = Self::acc_reward_per_share();
-			let precision = Self::precision();
-
-			Deposits::<T>::mutate(&who, |maybe_info| {
-				match maybe_info {
-					Some(info) => {
-						info.amount = info.amount.saturating_add(amount);
-						info.reward_debt = acc_per_share.saturating_mul(info.amount) / precision;
-						info.deposit_block = frame_system::Pallet::<T>::block_number();
-					},
-					None => {
-						let reward_debt = acc_per_share.saturating_mul(amount) / precision;
-						*maybe_info = Some(DepositInfo {
-							amount,
-							deposit_block: frame_system::Pallet::<T>::block_number(),
-							reward_debt,
-						});
-					},
-				}
-			});
-
-			TotalDeposited::<T>::mutate(|v| *v = v.saturating_add(amount));
+			T::Currency::transfer(&who, &pool_account, amount, Preservation::Expendable)?;
 
-			Ok(())
+			Self::add_deposit(&who, amount, LockTerm::default())?;
+			Self::mint_receipt(&who, amount)
 		}
 
 		/// Withdraw tokens and rewards from the pool
 		///
+		/// Rewards are paid out at once. With an unbonding period configured, the withdrawn
+		/// pooled tokens are only paid out by `withdraw_unbonded` once the period has passed,
+		/// vesting tokens on hold are always released at once. The exit fee is charged on the
+		/// withdrawn pooled tokens.
+		///
 		/// The dispatch origin for this call must be _Signed_.
 		///
 		/// - `amount`: The amount of deposited tokens to withdraw (None for full withdrawal)
@@ -172,67 +972,106 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			amount: Option<BalanceOf<T>>,
 		) -> DispatchResult {
-			let who = frame_system::Pallet::<T>::ensure_signed(origin)?;
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused()?;
 			let mut info = Deposits::<T>::get(&who).ok_or(Error::<T>::NoDeposit)?;
 
 			Self::update_pool()?;
 
-			let acc_per_share = Self::acc_reward_per_share();
-			let precision = Self::precision();
-			let pending = Self::calculate_pending_rewards(&who)?;
+			let mut pool = Self::native_pool();
+			let pending = pool.pending(&info);
 
 			let withdraw_amount = amount.unwrap_or(info.amount);
 			ensure!(!withdraw_amount.is_zero(), Error::<T>::ZeroAmount);
 			ensure!(withdraw_amount <= info.amount, Error::<T>::InsufficientBalance);
+			ensure!(
+				frame_system::Pallet::<T>::block_number() >= info.unlock_block,
+				Error::<T>::DepositLocked
+			);
+
+			// Pooled tokens are withdrawn first, vesting tokens on hold last.
+			let locked = LockedDeposits::<T>::get(&who);
+			let pooled = info.amount.saturating_sub(locked);
+			let released = withdraw_amount.saturating_sub(pooled);
 
 			let pool_account = Self::account_id();
-			let pool_balance = T::Currency::free_balance(&pool_account);
-			let total_payout = withdraw_amount.saturating_add(pending);
-			ensure!(pool_balance >= total_payout, Error::<T>::InsufficientPoolBalance);
+			let pool_balance = Self::pool_balance();
+			let principal = withdraw_amount.saturating_sub(released);
+			let fee = Self::exit_fee_at(info.deposit_block, principal);
+			ensure!(Self::receipt_balance(&who) >= principal, Error::<T>::InsufficientReceipts);
+			let unbonding = !T::UnbondingPeriod::get().is_zero();
+			let payout = if unbonding { pending } else { principal.saturating_add(pending) };
+			ensure!(pool_balance >= payout, Error::<T>::InsufficientPoolBalance);
 
-			info.amount = info.amount.saturating_sub(withdraw_amount);
+			// Shares are removed in proportion to the withdrawn part of the deposit.
+			let shares = info.shares;
+			pool.unstake(&mut info, withdraw_amount);
+			Self::settle_asset_rewards(&Depositor::Account(who.clone()), &who, shares, info.shares)?;
 			if info.amount.is_zero() {
 				Deposits::<T>::remove(&who);
+				Self::refund_entry_deposit(&Depositor::Account(who.clone()))?;
 			} else {
-				info.reward_debt = acc_per_share.saturating_mul(info.amount) / precision;
 				Deposits::<T>::insert(&who, &info);
 			}
+			Self::put_native_pool(pool);
 
-			TotalDeposited::<T>::mutate(|v| *v = v.saturating_sub(withdraw_amount));
-			TotalRewards::<T>::mutate(|r| *r = r.saturating_sub(pending));
+			Self::burn_receipt(&who, principal)?;
+			let fee = Self::distribute_exit_fee(fee)?;
+			let principal = principal.saturating_sub(fee);
 
-			T::Currency::transfer(&pool_account, &who, total_payout, ExistenceRequirement::AllowDeath)?;
+			if !released.is_zero() {
+				T::Currency::release(
+					&HoldReason::LockedDeposit.into(),
+					&who,
+					released,
+					Precision::Exact,
+				)?;
+				LockedDeposits::<T>::mutate(&who, |v| *v = v.saturating_sub(released));
+			}
+			if unbonding {
+				Self::unbond(&who, principal)?;
+			} else if !principal.is_zero() {
+				T::Currency::transfer(&pool_account, &who, principal, Preservation::Expendable)?;
+			}
+			Self::pay_depositor_reward(&who, pending)?;
 
+			T::OnPoolChange::on_withdraw(&who, withdraw_amount);
 			Ok(())
 		}
 
 		/// Claim pending rewards without withdrawing deposit
 		///
+		/// Pays out the native rewards and the rewards in every reward asset.
+		///
 		/// The dispatch origin for this call must be _Signed_.
 		#[pallet::call_index(2)]
 		#[pallet::weight({10_000})]
 		pub fn claim_rewards(origin: OriginFor<T>) -> DispatchResult {
-			let who = frame_system::Pallet::<T>::ensure_signed(origin)?;
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused()?;
 			let mut info = Deposits::<T>::get(&who).ok_or(Error::<T>::NoDeposit)?;
 
 			Self::update_pool()?;
 
 			let pending = Self::calculate_pending_rewards(&who)?;
-			ensure!(!pending.is_zero(), Error::<T>::ZeroAmount);
+			let depositor = Depositor::Account(who.clone());
+			ensure!(
+				!pending.is_zero() ||
+					Self::pending_asset_rewards_of(&depositor, info.shares)
+						.iter()
+						.any(|(_, amount)| !amount.is_zero()),
+				Error::<T>::ZeroAmount
+			);
+			Self::settle_asset_rewards(&depositor, &who, info.shares, info.shares)?;
 
-			let pool_account = Self::account_id();
-			let pool_balance = T::Currency::free_balance(&pool_account);
+			let pool_balance = Self::pool_balance();
 			ensure!(pool_balance >= pending, Error::<T>::InsufficientPoolBalance);
 
-			let acc_per_share = Self::acc_reward_per_share();
-			let precision = Self::precision();
-			info.reward_debt = acc_per_share.saturating_mul(info.amount) / precision;
+			let mut pool = Self::native_pool();
+			pool.settle(&mut info);
 			Deposits::<T>::insert(&who, &info);
-
-			TotalRewards::<T>::mutate(|r| *r = r.saturating_sub(pending));
-			T::Currency::transfer(&pool_account, &who, pending, ExistenceRequirement::AllowDeath)?;
-
-			Ok(())
+			Self::put_native_pool(pool);
+			Self::pay_depositor_reward(&who, pending)
 		}
 
 		/// Deposit rewards into the pool (team only)
@@ -247,53 +1086,2123 @@ pub mod pallet {
 			amount: BalanceOf<T>,
 		) -> DispatchResult {
 			let who = T::RewardOrigin::ensure_origin(origin)?;
-			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+			Self::do_deposit_rewards(&who, amount)
+		}
 
+		/// Commit vesting tokens to the pool without unlocking them
+		///
+		/// The tokens are put on hold in the caller's account and earn rewards like any other
+		/// deposit. Withdrawing them releases the hold, after which the vesting freeze applies
+		/// to them again.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `amount`: The amount of vesting tokens to commit
+		#[pallet::call_index(4)]
+		#[pallet::weight({10_000})]
+		pub fn deposit_locked(
+			origin: OriginFor<T>,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused()?;
+			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+
+			let vesting = T::Currency::balance_frozen(&T::VestingFreezeId::get(), &who);
+			let committed = LockedDeposits::<T>::get(&who);
+			ensure!(
+				vesting.saturating_sub(committed) >= amount,
+				Error::<T>::InsufficientLockedBalance
+			);
+			Self::ensure_deposit_allowed(Self::deposited_by(&who), amount)?;
+
+			Self::update_pool()?;
+
+			T::Currency::hold(&HoldReason::LockedDeposit.into(), &who, amount)?;
+			LockedDeposits::<T>::mutate(&who, |v| *v = v.saturating_add(amount));
+
+			Self::add_deposit(&who, amount, LockTerm::default())
+		}
+
+		/// Set the curve over which rewards vest, or pay them out liquid with `None`
+		///
+		/// The dispatch origin for this call must be from `AdminOrigin`.
+		///
+		/// - `curve`: The vesting curve applied to rewards paid out from now on
+		#[pallet::call_index(5)]
+		#[pallet::weight({10_000})]
+		pub fn set_reward_vesting(
+			origin: OriginFor<T>,
+			curve: Option<VestingCurve<BlockNumberFor<T>>>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			if let Some(curve) = &curve {
+				ensure!(
+					!curve.period.is_zero() && curve.period_count > 0,
+					Error::<T>::InvalidVestingCurve
+				);
+			}
+			RewardVestingCurve::<T>::set(curve);
+			Ok(())
+		}
+
+		/// Deposit native tokens into the pool, locked for one of the `LockTerms`
+		///
+		/// The deposit earns rewards according to the multiplier of the lock term and cannot
+		/// be withdrawn until the term has passed, when its rewards are paid out and the
+		/// multiplier ends. Adding to an existing deposit keeps the later of the two unlock
+		/// blocks for the whole deposit.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `amount`: The amount of tokens to deposit
+		/// - `term`: The index of the lock term in `LockTerms`
+		#[pallet::call_index(6)]
+		#[pallet::weight({10_000})]
+		pub fn deposit_for_term(
+			origin: OriginFor<T>,
+			amount: BalanceOf<T>,
+			term: u32,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused()?;
+			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+			let term = T::LockTerms::get()
+				.get(term as usize)
+				.copied()
+				.ok_or(Error::<T>::UnknownLockTerm)?;
+
+			let free_balance =
+				T::Currency::reducible_balance(&who, Preservation::Expendable, Fortitude::Polite);
+			ensure!(free_balance >= amount, Error::<T>::InsufficientBalance);
+			Self::ensure_deposit_allowed(Self::deposited_by(&who), amount)?;
+
+			Self::update_pool()?;
+
+			let pool_account = Self::account_id();
+			T::Currency::transfer(&who, &pool_account, amount, Preservation::Expendable)?;
+
+			Self::add_deposit(&who, amount, term)?;
+			Self::mint_receipt(&who, amount)
+		}
+
+		/// Withdraw all unbonding tokens whose unbonding period has passed
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		#[pallet::call_index(7)]
+		#[pallet::weight({10_000})]
+		pub fn withdraw_unbonded(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let now = frame_system::Pallet::<T>::block_number();
+
+			let mut unbonded = BalanceOf::<T>::zero();
+			Unbonding::<T>::mutate_exists(&who, |maybe_chunks| {
+				if let Some(chunks) = maybe_chunks {
+					chunks.retain(|chunk| {
+						let ready = chunk.unlock_block <= now;
+						if ready {
+							unbonded = unbonded.saturating_add(chunk.value);
+						}
+						!ready
+					});
+					if chunks.is_empty() {
+						*maybe_chunks = None;
+					}
+				}
+			});
+			ensure!(!unbonded.is_zero(), Error::<T>::NoUnbondedFunds);
+			ensure!(Self::pool_balance() >= unbonded, Error::<T>::InsufficientPoolBalance);
+
+			T::Currency::transfer(&Self::account_id(), &who, unbonded, Preservation::Expendable)?;
+
+			Ok(())
+		}
+
+		/// Move unbonding tokens back into the deposit
+		///
+		/// The most recently withdrawn tokens are rebonded first. They are added to the
+		/// deposit without a lock term.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `amount`: The amount of unbonding tokens to rebond
+		#[pallet::call_index(8)]
+		#[pallet::weight({10_000})]
+		pub fn rebond(
+			origin: OriginFor<T>,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused()?;
+			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+
+			let mut chunks = Unbonding::<T>::get(&who);
+			let unbonding = chunks
+				.iter()
+				.fold(BalanceOf::<T>::zero(), |acc, chunk| acc.saturating_add(chunk.value));
+			ensure!(unbonding >= amount, Error::<T>::InsufficientUnbonding);
+			Self::ensure_deposit_allowed(Self::deposited_by(&who), amount)?;
+
+			let mut remaining = amount;
+			while let Some(last) = chunks.last_mut() {
+				if last.value > remaining {
+					last.value = last.value.saturating_sub(remaining);
+					break;
+				}
+				remaining = remaining.saturating_sub(last.value);
+				chunks.pop();
+			}
+			if chunks.is_empty() {
+				Unbonding::<T>::remove(&who);
+			} else {
+				Unbonding::<T>::insert(&who, chunks);
+			}
+
+			Self::update_pool()?;
+			Self::add_deposit(&who, amount, LockTerm::default())?;
+			Self::mint_receipt(&who, amount)
+		}
+
+		/// Set the fee charged on withdrawals, or remove it with `None`
+		///
+		/// The dispatch origin for this call must be from `AdminOrigin`.
+		///
+		/// - `fee`: The exit fee applied to withdrawals from now on
+		#[pallet::call_index(9)]
+		#[pallet::weight({10_000})]
+		pub fn set_exit_fee(
+			origin: OriginFor<T>,
+			fee: Option<ExitFee<BlockNumberFor<T>>>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			if let Some(fee) = &fee {
+				ensure!(!fee.decay_period.is_zero(), Error::<T>::InvalidExitFee);
+			}
+			ExitFeeSchedule::<T>::set(fee);
+			Ok(())
+		}
+
+		/// Deposit native tokens into the compounding vault
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `amount`: The amount of tokens to deposit
+		#[pallet::call_index(10)]
+		#[pallet::weight({10_000})]
+		pub fn deposit_compounding(
+			origin: OriginFor<T>,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused()?;
+			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+
+			let free_balance =
+				T::Currency::reducible_balance(&who, Preservation::Expendable, Fortitude::Polite);
+			ensure!(free_balance >= amount, Error::<T>::InsufficientBalance);
+			Self::ensure_deposit_allowed(Self::deposited_by(&who), amount)?;
+
+			Self::update_pool()?;
+
+			let shares = Self::vault_shares_for(amount, Self::vault_balance());
+			ensure!(!shares.is_zero(), Error::<T>::ZeroAmount);
+
+			let pool_account = Self::account_id();
+			T::Currency::transfer(&who, &pool_account, amount, Preservation::Expendable)?;
+			Self::add_deposit(&Self::vault_account(), amount, LockTerm::default())?;
+			if !CompoundDeposits::<T>::contains_key(&who) {
+				Self::take_entry_deposit(Depositor::Compounding(who.clone()), &who)?;
+			}
+
+			let now = frame_system::Pallet::<T>::block_number();
+			CompoundDeposits::<T>::mutate(&who, |maybe_deposit| {
+				let deposit = maybe_deposit
+					.get_or_insert(CompoundDeposit { shares: Zero::zero(), deposit_block: now });
+				deposit.shares = deposit.shares.saturating_add(shares);
+				deposit.deposit_block = now;
+			});
+			TotalCompoundShares::<T>::mutate(|v| *v = v.saturating_add(shares));
+
+			T::OnPoolChange::on_deposit(&who, amount);
+			Ok(())
+		}
+
+		/// Withdraw tokens from the compounding vault
+		///
+		/// The withdrawn tokens are subject to the unbonding period and the exit fee like
+		/// withdrawals of regular deposits.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `shares`: The amount of vault shares to redeem (None for full withdrawal)
+		#[pallet::call_index(11)]
+		#[pallet::weight({10_000})]
+		pub fn withdraw_compounding(
+			origin: OriginFor<T>,
+			shares: Option<BalanceOf<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused()?;
+			let mut deposit = CompoundDeposits::<T>::get(&who).ok_or(Error::<T>::NoDeposit)?;
+
+			Self::update_pool()?;
+
+			let shares = shares.unwrap_or(deposit.shares);
+			ensure!(!shares.is_zero(), Error::<T>::ZeroAmount);
+			ensure!(shares <= deposit.shares, Error::<T>::InsufficientBalance);
+
+			let vault = Self::vault_account();
+			let mut info = Deposits::<T>::get(&vault).ok_or(Error::<T>::NoDeposit)?;
+			let amount = if shares == Self::total_compound_shares() {
+				info.amount
+			} else {
+				Self::vault_amount_for(shares, info.amount)
+			};
+			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+			let fee = Self::exit_fee_at(deposit.deposit_block, amount);
+
+			let unbonding = !T::UnbondingPeriod::get().is_zero();
+			if !unbonding {
+				ensure!(Self::pool_balance() >= amount, Error::<T>::InsufficientPoolBalance);
+			}
+
+			// The vault rewards were just compounded, so none are pending.
+			let mut pool = Self::native_pool();
+			pool.unstake(&mut info, amount);
+			if info.amount.is_zero() {
+				Deposits::<T>::remove(&vault);
+			} else {
+				Deposits::<T>::insert(&vault, &info);
+			}
+			Self::put_native_pool(pool);
+
+			deposit.shares = deposit.shares.saturating_sub(shares);
+			if deposit.shares.is_zero() {
+				CompoundDeposits::<T>::remove(&who);
+				Self::refund_entry_deposit(&Depositor::Compounding(who.clone()))?;
+			} else {
+				CompoundDeposits::<T>::insert(&who, &deposit);
+			}
+			TotalCompoundShares::<T>::mutate(|v| *v = v.saturating_sub(shares));
+			T::OnPoolChange::on_withdraw(&who, amount);
+
+			let fee = Self::distribute_exit_fee(fee)?;
+			let amount = amount.saturating_sub(fee);
+			if unbonding {
+				Self::unbond(&who, amount)
+			} else {
+				T::Currency::transfer(&Self::account_id(), &who, amount, Preservation::Expendable)
+					.map(|_| ())
+			}
+		}
+
+		/// Transfer receipt tokens together with the deposit backing them
+		///
+		/// Pending rewards of both accounts are paid out first, all later rewards on the
+		/// transferred deposit go to `dest`.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `dest`: The account receiving the receipt tokens
+		/// - `amount`: The amount of receipt tokens to transfer
+		#[pallet::call_index(12)]
+		#[pallet::weight({10_000})]
+		pub fn transfer_receipt(
+			origin: OriginFor<T>,
+			dest: T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+			ensure!(who != dest, Error::<T>::SelfTransfer);
+			ensure!(Self::receipt_balance(&who) >= amount, Error::<T>::InsufficientReceipts);
+
+			Self::update_pool()?;
+			Self::on_receipt_transfer(&who, &dest, amount)?;
+
+			<T::Assets as fungibles::Mutate<_>>::transfer(
+				T::ReceiptAssetId::get(),
+				&who,
+				&dest,
+				amount,
+				Preservation::Expendable,
+			)?;
+			Ok(())
+		}
+
+		/// Add an asset rewards can be paid in
+		///
+		/// The dispatch origin for this call must be from `AdminOrigin`.
+		///
+		/// - `asset`: The asset to add
+		#[pallet::call_index(17)]
+		#[pallet::weight({10_000})]
+		pub fn add_reward_asset(origin: OriginFor<T>, asset: T::AssetId) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			RewardAssets::<T>::try_mutate(|assets| {
+				ensure!(!assets.contains(&asset), Error::<T>::RewardAssetExists);
+				assets.try_push(asset).map_err(|_| Error::<T>::TooManyRewardAssets)?;
+				Ok(())
+			})
+		}
+
+		/// Deposit rewards in a reward asset into the pool
+		///
+		/// The rewards are shared out over the regular deposits and positions, the compounding
+		/// vault only earns native rewards.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `asset`: The reward asset to deposit
+		/// - `amount`: The amount of rewards to deposit
+		#[pallet::call_index(18)]
+		#[pallet::weight({10_000})]
+		pub fn deposit_asset_rewards(
+			origin: OriginFor<T>,
+			asset: T::AssetId,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused()?;
+			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+			ensure!(Self::reward_assets().contains(&asset), Error::<T>::UnknownRewardAsset);
+			let total_shares =
+				Self::total_shares().saturating_sub(Self::shares_of(&Self::vault_account()));
+			ensure!(!total_shares.is_zero(), Error::<T>::NoDepositors);
+
+			let pool_account = Self::account_id();
+			Self::ensure_account_exists(&pool_account);
+			<T::Assets as fungibles::Mutate<_>>::transfer(
+				asset.clone(),
+				&who,
+				&pool_account,
+				amount,
+				Preservation::Expendable,
+			)?;
+
+			let increment = PoolState::acc_increment(amount, total_shares);
+			AssetAccRewardPerShare::<T>::mutate(&asset, |v| *v = v.saturating_add(increment));
+			Ok(())
+		}
+
+		/// Open a pool for a staking token
+		///
+		/// The dispatch origin for this call must be from `AdminOrigin`.
+		///
+		/// - `token`: The staking token of the pool
+		#[pallet::call_index(19)]
+		#[pallet::weight({10_000})]
+		pub fn create_token_pool(origin: OriginFor<T>, token: T::StakingToken) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(!TokenPools::<T>::contains_key(&token), Error::<T>::TokenPoolExists);
+
+			Self::ensure_account_exists(&Self::token_pool_account(&token));
+			TokenPools::<T>::insert(token, PoolState::default());
+			Ok(())
+		}
+
+		/// Deposit staking tokens into a token pool
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `token`: The staking token of the pool
+		/// - `amount`: The amount of tokens to deposit
+		#[pallet::call_index(20)]
+		#[pallet::weight({10_000})]
+		pub fn deposit_token(
+			origin: OriginFor<T>,
+			token: T::StakingToken,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::add_token_deposit(&who, token, amount, LockTerm::default())
+		}
+
+		/// Deposit staking tokens into a token pool, locked for one of the `LockTerms`
+		///
+		/// The lock terms work as for `deposit_for_term`.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `token`: The staking token of the pool
+		/// - `amount`: The amount of tokens to deposit
+		/// - `term`: The index of the lock term in `LockTerms`
+		#[pallet::call_index(32)]
+		#[pallet::weight({10_000})]
+		pub fn deposit_token_for_term(
+			origin: OriginFor<T>,
+			token: T::StakingToken,
+			amount: BalanceOf<T>,
+			term: u32,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let term = T::LockTerms::get()
+				.get(term as usize)
+				.copied()
+				.ok_or(Error::<T>::UnknownLockTerm)?;
+			Self::add_token_deposit(&who, token, amount, term)
+		}
+
+		/// Withdraw staking tokens and rewards from a token pool
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `token`: The staking token of the pool
+		/// - `amount`: The amount of tokens to withdraw (None for full withdrawal)
+		#[pallet::call_index(21)]
+		#[pallet::weight({10_000})]
+		pub fn withdraw_token(
+			origin: OriginFor<T>,
+			token: T::StakingToken,
+			amount: Option<BalanceOf<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused()?;
+			let mut pool = TokenPools::<T>::get(&token).ok_or(Error::<T>::UnknownTokenPool)?;
+			let mut info = TokenDeposits::<T>::get(&token, &who).ok_or(Error::<T>::NoDeposit)?;
+
+			let withdraw_amount = amount.unwrap_or(info.amount);
+			ensure!(!withdraw_amount.is_zero(), Error::<T>::ZeroAmount);
+			ensure!(withdraw_amount <= info.amount, Error::<T>::InsufficientBalance);
+			ensure!(
+				frame_system::Pallet::<T>::block_number() >= info.unlock_block,
+				Error::<T>::DepositLocked
+			);
+
+			let pending = pool.unstake(&mut info, withdraw_amount);
+			if info.amount.is_zero() {
+				TokenDeposits::<T>::remove(&token, &who);
+				Self::refund_entry_deposit(&Depositor::Token(token.clone(), who.clone()))?;
+			} else {
+				TokenDeposits::<T>::insert(&token, &who, info);
+			}
+			TokenPools::<T>::insert(&token, pool);
+
+			let pool_account = Self::token_pool_account(&token);
+			<T::StakingAssets as fungibles::Mutate<_>>::transfer(
+				token.clone(),
+				&pool_account,
+				&who,
+				withdraw_amount,
+				Preservation::Expendable,
+			)?;
+			Self::pay_reward_from(&pool_account, &who, pending)?;
+
+			if !pending.is_zero() {
+				T::OnPoolChange::on_token_claim(&token, &who, pending);
+			}
+			T::OnPoolChange::on_token_withdraw(&token, &who, withdraw_amount);
+			Ok(())
+		}
+
+		/// Claim pending rewards of a token pool without withdrawing
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `token`: The staking token of the pool
+		#[pallet::call_index(22)]
+		#[pallet::weight({10_000})]
+		pub fn claim_token_rewards(origin: OriginFor<T>, token: T::StakingToken) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused()?;
+			ensure!(TokenPools::<T>::contains_key(&token), Error::<T>::UnknownTokenPool);
+			ensure!(TokenDeposits::<T>::contains_key(&token, &who), Error::<T>::NoDeposit);
+
+			let pending = Self::settle_token_rewards(&token, &who)?;
+			ensure!(!pending.is_zero(), Error::<T>::ZeroAmount);
+			Ok(())
+		}
+
+		/// Deposit native rewards into a token pool (team only)
+		///
+		/// The dispatch origin for this call must be from `RewardOrigin`.
+		///
+		/// - `token`: The staking token of the pool
+		/// - `amount`: The amount of rewards to deposit
+		#[pallet::call_index(23)]
+		#[pallet::weight({10_000})]
+		pub fn deposit_token_rewards(
+			origin: OriginFor<T>,
+			token: T::StakingToken,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = T::RewardOrigin::ensure_origin(origin)?;
+			Self::ensure_not_paused()?;
+			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+			let mut pool = TokenPools::<T>::get(&token).ok_or(Error::<T>::UnknownTokenPool)?;
+			ensure!(!pool.total_shares.is_zero(), Error::<T>::NoDepositors);
+
+			let pool_account = Self::token_pool_account(&token);
+			T::Currency::transfer(&who, &pool_account, amount, Preservation::Expendable)?;
+
+			pool.distribute(amount);
+			TokenPools::<T>::insert(&token, pool);
+
+			T::OnPoolChange::on_token_rewards_deposited(&token, amount);
+			Ok(())
+		}
+
+		/// Set the limits on deposits into a token pool
+		///
+		/// The dispatch origin for this call must be from `AdminOrigin`.
+		///
+		/// - `token`: The staking token of the pool
+		/// - `limits`: The limits applied to deposits from now on
+		#[pallet::call_index(33)]
+		#[pallet::weight({10_000})]
+		pub fn set_token_pool_limits(
+			origin: OriginFor<T>,
+			token: T::StakingToken,
+			limits: DepositLimits<BalanceOf<T>>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(TokenPools::<T>::contains_key(&token), Error::<T>::UnknownTokenPool);
+			if limits == DepositLimits::default() {
+				TokenPoolLimits::<T>::remove(&token);
+			} else {
+				TokenPoolLimits::<T>::insert(&token, limits);
+			}
+			Ok(())
+		}
+
+		/// Set the limits on deposits into the native pool
+		///
+		/// The dispatch origin for this call must be from `AdminOrigin`.
+		///
+		/// - `min_deposit`: The minimum amount of a single deposit
+		/// - `max_per_account`: The maximum an account can have deposited (None for no limit)
+		/// - `pool_cap`: The maximum total deposited (None for no limit)
+		#[pallet::call_index(24)]
+		#[pallet::weight({10_000})]
+		pub fn set_deposit_limits(
+			origin: OriginFor<T>,
+			min_deposit: BalanceOf<T>,
+			max_per_account: Option<BalanceOf<T>>,
+			pool_cap: Option<BalanceOf<T>>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			MinDeposit::<T>::put(min_deposit);
+			MaxDepositPerAccount::<T>::set(max_per_account);
+			PoolCap::<T>::set(pool_cap);
+			Ok(())
+		}
+
+		/// Pause the native pool and the token pools
+		///
+		/// The dispatch origin for this call must be from `AdminOrigin`.
+		#[pallet::call_index(25)]
+		#[pallet::weight({10_000})]
+		pub fn pause(origin: OriginFor<T>) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Paused::<T>::put(true);
+			Ok(())
+		}
+
+		/// Unpause the native pool and the token pools
+		///
+		/// The dispatch origin for this call must be from `AdminOrigin`.
+		#[pallet::call_index(26)]
+		#[pallet::weight({10_000})]
+		pub fn unpause(origin: OriginFor<T>) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Paused::<T>::kill();
+			Ok(())
+		}
+
+		/// Withdraw the whole regular deposit while the pool is paused, forfeiting its rewards
+		///
+		/// Lock terms, the unbonding period and the exit fee do not apply. Vesting tokens on
+		/// hold are released.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		#[pallet::call_index(27)]
+		#[pallet::weight({10_000})]
+		pub fn emergency_withdraw(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::paused(), Error::<T>::NotPaused);
+			let mut info = Deposits::<T>::take(&who).ok_or(Error::<T>::NoDeposit)?;
+
+			let released = LockedDeposits::<T>::take(&who);
+			let pooled = info.amount.saturating_sub(released);
+			ensure!(Self::pool_balance() >= pooled, Error::<T>::InsufficientPoolBalance);
+			ensure!(Self::receipt_balance(&who) >= pooled, Error::<T>::InsufficientReceipts);
+
+			let mut pool = Self::native_pool();
+			let amount = info.amount;
+			pool.forfeit(&mut info, amount);
+			Self::put_native_pool(pool);
+			let _ = AssetRewardDebts::<T>::clear_prefix(
+				Depositor::Account(who.clone()),
+				T::MaxRewardAssets::get(),
+				None,
+			);
+			Self::refund_entry_deposit(&Depositor::Account(who.clone()))?;
+			Self::burn_receipt(&who, pooled)?;
+			T::OnPoolChange::on_withdraw(&who, amount);
+
+			if !released.is_zero() {
+				T::Currency::release(
+					&HoldReason::LockedDeposit.into(),
+					&who,
+					released,
+					Precision::Exact,
+				)?;
+			}
+			if !pooled.is_zero() {
+				T::Currency::transfer(&Self::account_id(), &who, pooled, Preservation::Expendable)?;
+			}
+			Ok(())
+		}
+
+		/// Withdraw a position while the pool is paused, forfeiting its rewards and burning its
+		/// NFT item
+		///
+		/// Lock terms, the unbonding period and the exit fee do not apply.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the owner of the position.
+		///
+		/// - `id`: The position to withdraw
+		#[pallet::call_index(34)]
+		#[pallet::weight({10_000})]
+		pub fn emergency_withdraw_position(origin: OriginFor<T>, id: PositionId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::paused(), Error::<T>::NotPaused);
+			let collection = Self::ensure_position_owner(&who, id)?;
+			let mut info = Positions::<T>::take(id).ok_or(Error::<T>::UnknownPosition)?;
+			let amount = info.amount;
+			ensure!(Self::pool_balance() >= amount, Error::<T>::InsufficientPoolBalance);
+
+			let mut pool = Self::native_pool();
+			pool.forfeit(&mut info, amount);
+			Self::put_native_pool(pool);
+			let _ = AssetRewardDebts::<T>::clear_prefix(
+				Depositor::Position(id),
+				T::MaxRewardAssets::get(),
+				None,
+			);
+			Self::refund_entry_deposit(&Depositor::Position(id))?;
+			T::Nfts::enable_transfer(&collection, &id)?;
+			<T::Nfts as nonfungibles_v2::Mutate<_, _>>::burn(&collection, &id, Some(&who))?;
+			T::OnPoolChange::on_withdraw(&who, amount);
+
+			T::Currency::transfer(&Self::account_id(), &who, amount, Preservation::Expendable)?;
+			Ok(())
+		}
+
+		/// Withdraw all compounding vault shares while the pool is paused
+		///
+		/// The vault rewards not compounded yet are forfeited. The unbonding period and the
+		/// exit fee do not apply.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		#[pallet::call_index(35)]
+		#[pallet::weight({10_000})]
+		pub fn emergency_withdraw_compounding(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::paused(), Error::<T>::NotPaused);
+			let deposit = CompoundDeposits::<T>::take(&who).ok_or(Error::<T>::NoDeposit)?;
+
+			let vault = Self::vault_account();
+			let mut info = Deposits::<T>::get(&vault).ok_or(Error::<T>::NoDeposit)?;
+			let amount = if deposit.shares == Self::total_compound_shares() {
+				info.amount
+			} else {
+				Self::vault_amount_for(deposit.shares, info.amount)
+			};
+			ensure!(Self::pool_balance() >= amount, Error::<T>::InsufficientPoolBalance);
+
+			let mut pool = Self::native_pool();
+			pool.forfeit(&mut info, amount);
+			Self::put_native_pool(pool);
+			if info.amount.is_zero() {
+				Deposits::<T>::remove(&vault);
+			} else {
+				Deposits::<T>::insert(&vault, &info);
+			}
+			TotalCompoundShares::<T>::mutate(|v| *v = v.saturating_sub(deposit.shares));
+			Self::refund_entry_deposit(&Depositor::Compounding(who.clone()))?;
+			T::OnPoolChange::on_withdraw(&who, amount);
+
+			if !amount.is_zero() {
+				T::Currency::transfer(&Self::account_id(), &who, amount, Preservation::Expendable)?;
+			}
+			Ok(())
+		}
+
+		/// Withdraw the whole deposit in a token pool while the pools are paused, forfeiting
+		/// its rewards
+		///
+		/// Lock terms do not apply.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `token`: The staking token of the pool
+		#[pallet::call_index(36)]
+		#[pallet::weight({10_000})]
+		pub fn emergency_withdraw_token(
+			origin: OriginFor<T>,
+			token: T::StakingToken,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::paused(), Error::<T>::NotPaused);
+			let mut pool = TokenPools::<T>::get(&token).ok_or(Error::<T>::UnknownTokenPool)?;
+			let mut info = TokenDeposits::<T>::take(&token, &who).ok_or(Error::<T>::NoDeposit)?;
+			let amount = info.amount;
+
+			pool.forfeit(&mut info, amount);
+			TokenPools::<T>::insert(&token, pool);
+			Self::refund_entry_deposit(&Depositor::Token(token.clone(), who.clone()))?;
+			T::OnPoolChange::on_token_withdraw(&token, &who, amount);
+
+			let pool_account = Self::token_pool_account(&token);
+			<T::StakingAssets as fungibles::Mutate<_>>::transfer(
+				token,
+				&pool_account,
+				&who,
+				amount,
+				Preservation::Expendable,
+			)?;
+			Ok(())
+		}
+
+		/// Set where the native rewards of the caller's regular deposit are paid
+		///
+		/// Applies to `claim_rewards`, `withdraw` and the rewards paid out when adding to the
+		/// deposit. Compounded rewards are added to the deposit without a lock term and mint
+		/// receipt tokens.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `dest`: The reward destination
+		#[pallet::call_index(28)]
+		#[pallet::weight({10_000})]
+		pub fn set_reward_destination(
+			origin: OriginFor<T>,
+			dest: RewardDestination<T::AccountId>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			if dest == RewardDestination::Stay {
+				RewardDestinations::<T>::remove(&who);
+			} else {
+				RewardDestinations::<T>::insert(&who, dest);
+			}
+			Ok(())
+		}
+
+		/// Set the amount moved from the reward reserve into the native pool once a day
+		///
+		/// The dispatch origin for this call must be from `AdminOrigin`.
+		///
+		/// - `amount`: The daily amount (None to stop scheduled rewards)
+		#[pallet::call_index(29)]
+		#[pallet::weight({10_000})]
+		pub fn set_scheduled_reward(
+			origin: OriginFor<T>,
+			amount: Option<BalanceOf<T>>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			match amount {
+				Some(amount) => {
+					ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+					ScheduledReward::<T>::put(amount);
+				},
+				None => ScheduledReward::<T>::kill(),
+			}
+			Ok(())
+		}
+
+		/// Move the scheduled reward of `day` from the reward reserve into the pool
+		///
+		/// Submitted by the keeper offchain worker. Rewards are distributed at most once per
+		/// day, and only for the current day, so resubmissions fail.
+		///
+		/// The dispatch origin for this call must be from `RewardOrigin`.
+		///
+		/// - `day`: The current day, the block number divided by `BlocksPerDay`
+		#[pallet::call_index(30)]
+		#[pallet::weight({10_000})]
+		pub fn distribute_scheduled_reward(origin: OriginFor<T>, day: u32) -> DispatchResult {
+			T::RewardOrigin::ensure_origin(origin)?;
+			let amount = Self::scheduled_reward().ok_or(Error::<T>::NoScheduledReward)?;
+			ensure!(day == Self::current_day(), Error::<T>::InvalidRewardDay);
+			ensure!(
+				Self::last_reward_day().is_none_or(|last| last < day),
+				Error::<T>::RewardAlreadyDistributed
+			);
+			LastRewardDay::<T>::put(day);
+			Self::do_deposit_rewards(&T::RewardReserve::get(), amount)
+		}
+
+		/// Add an account to the reward team, or remove it
+		///
+		/// The dispatch origin for this call must be from `AdminOrigin`.
+		///
+		/// - `who`: The account to add or remove
+		/// - `member`: Whether the account is a member of the reward team
+		#[pallet::call_index(37)]
+		#[pallet::weight({10_000})]
+		pub fn set_reward_team_member(
+			origin: OriginFor<T>,
+			who: T::AccountId,
+			member: bool,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			if member {
+				RewardTeam::<T>::insert(&who, ());
+			} else {
+				RewardTeam::<T>::remove(&who);
+			}
+			Ok(())
+		}
+
+		/// Vest the rewards of the caller that could not be vested when they were paid
+		///
+		/// They are paid out liquid if no vesting curve is set anymore.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		#[pallet::call_index(31)]
+		#[pallet::weight({10_000})]
+		pub fn vest_held_rewards(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused()?;
+			let amount = HeldRewards::<T>::take(&who);
+			ensure!(!amount.is_zero(), Error::<T>::NoHeldRewards);
+			let pool_account = Self::account_id();
+			match Self::reward_vesting_curve() {
+				Some(curve) => T::RewardVesting::vest_reward(
+					&pool_account,
+					&who,
+					amount,
+					curve.period,
+					curve.period_count,
+				),
+				None => T::Currency::transfer(&pool_account, &who, amount, Preservation::Expendable)
+					.map(|_| ()),
+			}
+		}
+
+		/// Deposit native tokens as a new position, minted to the caller as an NFT item
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `amount`: The amount of tokens to deposit
+		/// - `term`: The index of the lock term in `LockTerms`
+		#[pallet::call_index(13)]
+		#[pallet::weight({10_000})]
+		pub fn deposit_position(
+			origin: OriginFor<T>,
+			amount: BalanceOf<T>,
+			term: u32,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused()?;
+			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+			let term = T::LockTerms::get()
+				.get(term as usize)
+				.copied()
+				.ok_or(Error::<T>::UnknownLockTerm)?;
+
+			let free_balance =
+				T::Currency::reducible_balance(&who, Preservation::Expendable, Fortitude::Polite);
+			ensure!(free_balance >= amount, Error::<T>::InsufficientBalance);
+			Self::ensure_deposit_allowed(Self::deposited_by(&who), amount)?;
+
+			Self::update_pool()?;
+
+			let pool_account = Self::account_id();
+			T::Currency::transfer(&who, &pool_account, amount, Preservation::Expendable)?;
+
+			let collection = Self::ensure_position_collection()?;
+			let id = NextPositionId::<T>::mutate(|id| {
+				let current = *id;
+				*id = id.saturating_add(1);
+				current
+			});
+			<T::Nfts as nonfungibles_v2::Mutate<_, _>>::mint_into(
+				&collection,
+				&id,
+				&who,
+				&T::NftItemConfig::default(),
+				false,
+			)?;
+			T::Nfts::disable_transfer(&collection, &id)?;
+
+			let now = frame_system::Pallet::<T>::block_number();
+			let shares = amount.saturating_mul(term.reward_multiplier.into()) / 100u32.into();
+			Self::settle_asset_rewards(&Depositor::Position(id), &who, Zero::zero(), shares)?;
+			Self::take_entry_deposit(Depositor::Position(id), &who)?;
+			let mut info = DepositInfo {
+				amount: Zero::zero(),
+				deposit_block: now,
+				reward_debt: Zero::zero(),
+				shares: Zero::zero(),
+				unlock_block: now.saturating_add(term.duration),
+			};
+			let mut pool = Self::native_pool();
+			pool.stake(&mut info, amount, shares);
+			Positions::<T>::insert(id, info);
+			Self::put_native_pool(pool);
+
+			if shares > amount {
+				let at = now.saturating_add(term.duration).max(now.saturating_add(One::one()));
+				Self::schedule_boost_expiry(Depositor::Position(id), at)?;
+			}
+			T::OnPoolChange::on_deposit(&who, amount);
+			Ok(())
+		}
+
+		/// Claim the pending rewards of a position
+		///
+		/// The dispatch origin for this call must be _Signed_ by the owner of the position.
+		///
+		/// - `id`: The position to claim the rewards of
+		#[pallet::call_index(14)]
+		#[pallet::weight({10_000})]
+		pub fn claim_position_rewards(origin: OriginFor<T>, id: PositionId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused()?;
+			Self::ensure_position_owner(&who, id)?;
+
+			Self::update_pool()?;
+			Self::settle_position(&who, id)
+		}
+
+		/// Withdraw a position with its rewards, burning its NFT item
+		///
+		/// The withdrawn tokens are subject to the unbonding period and the exit fee like
+		/// withdrawals of regular deposits.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the owner of the position.
+		///
+		/// - `id`: The position to withdraw
+		#[pallet::call_index(15)]
+		#[pallet::weight({10_000})]
+		pub fn withdraw_position(origin: OriginFor<T>, id: PositionId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused()?;
+			let collection = Self::ensure_position_owner(&who, id)?;
+			let info = Positions::<T>::get(id).ok_or(Error::<T>::UnknownPosition)?;
+			ensure!(
+				frame_system::Pallet::<T>::block_number() >= info.unlock_block,
+				Error::<T>::DepositLocked
+			);
+
+			Self::update_pool()?;
+			Self::settle_position(&who, id)?;
+
+			let unbonding = !T::UnbondingPeriod::get().is_zero();
+			if !unbonding {
+				ensure!(Self::pool_balance() >= info.amount, Error::<T>::InsufficientPoolBalance);
+			}
+			let fee = Self::exit_fee_at(info.deposit_block, info.amount);
+
+			Self::settle_asset_rewards(&Depositor::Position(id), &who, info.shares, Zero::zero())?;
+			let mut settled = Positions::<T>::take(id).ok_or(Error::<T>::UnknownPosition)?;
+			Self::refund_entry_deposit(&Depositor::Position(id))?;
+			let mut pool = Self::native_pool();
+			pool.unstake(&mut settled, info.amount);
+			Self::put_native_pool(pool);
+			T::Nfts::enable_transfer(&collection, &id)?;
+			<T::Nfts as nonfungibles_v2::Mutate<_, _>>::burn(&collection, &id, Some(&who))?;
+			T::OnPoolChange::on_withdraw(&who, info.amount);
+
+			let fee = Self::distribute_exit_fee(fee)?;
+			let amount = info.amount.saturating_sub(fee);
+			if unbonding {
+				Self::unbond(&who, amount)
+			} else {
+				T::Currency::transfer(&Self::account_id(), &who, amount, Preservation::Expendable)
+					.map(|_| ())
+			}
+		}
+
+		/// Transfer a position to another account
+		///
+		/// The pending rewards of the position are paid to the caller, all later rewards and
+		/// the right to withdraw go to `dest`.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the owner of the position.
+		///
+		/// - `id`: The position to transfer
+		/// - `dest`: The new owner of the position
+		#[pallet::call_index(16)]
+		#[pallet::weight({10_000})]
+		pub fn transfer_position(
+			origin: OriginFor<T>,
+			id: PositionId,
+			dest: T::AccountId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_paused()?;
+			let collection = Self::ensure_position_owner(&who, id)?;
+
+			Self::update_pool()?;
+			Self::settle_position(&who, id)?;
+
+			T::Nfts::enable_transfer(&collection, &id)?;
+			T::Nfts::transfer(&collection, &id, &dest)?;
+			T::Nfts::disable_transfer(&collection, &id)?;
+			let amount = Positions::<T>::get(id).map(|info| info.amount).unwrap_or_default();
+			T::OnPoolChange::on_withdraw(&who, amount);
+			T::OnPoolChange::on_deposit(&dest, amount);
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The account ID of the pool
+		pub fn account_id() -> T::AccountId {
+			T::PalletId::get().into_account_truncating()
+		}
+
+		/// Give the pallet account `who` a permanent provider reference if it does not exist yet,
+		/// so that it can hold assets without a native balance
+		fn ensure_account_exists(who: &T::AccountId) {
+			if !frame_system::Pallet::<T>::account_exists(who) {
+				frame_system::Pallet::<T>::inc_providers(who);
+			}
+		}
+
+		/// Settle the rewards of `from` and `to` and move `amount` of deposited tokens, backed by
+		/// receipt tokens being transferred, from `from` to `to`
+		///
+		/// The deposit of `from` must be unlocked and its lock term multiplier ended, so that
+		/// the moved tokens carry one share each.
+		pub fn on_receipt_transfer(
+			from: &T::AccountId,
+			to: &T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			Self::ensure_not_paused()?;
+			ensure!(*to != Self::vault_account(), Error::<T>::VaultTransfer);
+			Self::settle_rewards(from)?;
+			Self::settle_rewards(to)?;
+
+			let mut pool = Self::native_pool();
+			let mut sender = Deposits::<T>::get(from).ok_or(Error::<T>::NoDeposit)?;
+			ensure!(sender.amount >= amount, Error::<T>::InsufficientBalance);
+			// The pool total does not change, only the account limit of the receiver applies.
+			let limits = DepositLimits { pool_cap: None, ..Self::deposit_limits() };
+			Self::check_limits(&limits, pool.total_deposited, Self::deposited_by(to), amount)?;
+			ensure!(
+				frame_system::Pallet::<T>::block_number() >= sender.unlock_block &&
+					sender.shares <= sender.amount,
+				Error::<T>::DepositLocked
+			);
+			let receiver_shares = Self::shares_of(to);
+			Self::settle_asset_rewards(
+				&Depositor::Account(from.clone()),
+				from,
+				sender.shares,
+				sender.shares.saturating_sub(amount),
+			)?;
+			Self::settle_asset_rewards(
+				&Depositor::Account(to.clone()),
+				to,
+				receiver_shares,
+				receiver_shares.saturating_add(amount),
+			)?;
+			// Both deposits were just settled, so none of their rewards are pending.
+			pool.unstake(&mut sender, amount);
+			let deposit_block = sender.deposit_block;
+			if sender.amount.is_zero() {
+				Deposits::<T>::remove(from);
+				Self::refund_entry_deposit(&Depositor::Account(from.clone()))?;
+			} else {
+				Deposits::<T>::insert(from, &sender);
+			}
+
+			// The sender pays for the entry of the receiver, if it is new.
+			if !Deposits::<T>::contains_key(to) {
+				Self::take_entry_deposit(Depositor::Account(to.clone()), from)?;
+			}
+			let mut receiver = Deposits::<T>::get(to).unwrap_or(DepositInfo {
+				amount: Zero::zero(),
+				deposit_block,
+				reward_debt: Zero::zero(),
+				shares: Zero::zero(),
+				unlock_block: frame_system::Pallet::<T>::block_number(),
+			});
+			pool.stake(&mut receiver, amount, amount);
+			Deposits::<T>::insert(to, receiver);
+			Self::put_native_pool(pool);
+
+			T::OnPoolChange::on_withdraw(from, amount);
+			T::OnPoolChange::on_deposit(to, amount);
+			Ok(())
+		}
+
+		/// Pay out the pending rewards of `who`, if it has a deposit
+		fn settle_rewards(who: &T::AccountId) -> DispatchResult {
+			let Some(mut info) = Deposits::<T>::get(who) else { return Ok(()) };
+			let pending = Self::calculate_pending_rewards(who)?;
+			if pending.is_zero() {
+				return Ok(());
+			}
+			ensure!(Self::pool_balance() >= pending, Error::<T>::InsufficientPoolBalance);
+			let mut pool = Self::native_pool();
+			pool.settle(&mut info);
+			Deposits::<T>::insert(who, &info);
+			Self::put_native_pool(pool);
+			Self::pay_depositor_reward(who, pending)
+		}
+
+		/// The position collection, created with the pool account as owner if needed
+		fn ensure_position_collection() -> Result<T::CollectionId, DispatchError> {
+			if let Some(collection) = Self::position_collection() {
+				return Ok(collection);
+			}
 			let pool_account = Self::account_id();
-			T::Currency::transfer(&who, &pool_account, amount, ExistenceRequirement::AllowDeath)?;
+			let collection = <T::Nfts as nonfungibles_v2::Create<_, _>>::create_collection(
+				&pool_account,
+				&pool_account,
+				&T::NftCollectionConfig::default(),
+			)?;
+			PositionCollection::<T>::put(collection);
+			Ok(collection)
+		}
+
+		/// Ensure `who` owns the position `id`, returning the position collection
+		fn ensure_position_owner(
+			who: &T::AccountId,
+			id: PositionId,
+		) -> Result<T::CollectionId, DispatchError> {
+			let collection = Self::position_collection().ok_or(Error::<T>::UnknownPosition)?;
+			let owner = T::Nfts::owner(&collection, &id).ok_or(Error::<T>::UnknownPosition)?;
+			ensure!(&owner == who, Error::<T>::NotPositionOwner);
+			Ok(collection)
+		}
+
+		/// The pending rewards of the position `id`
+		pub fn pending_position_rewards(id: PositionId) -> BalanceOf<T> {
+			Positions::<T>::get(id)
+				.map(|info| Self::native_pool().pending(&info))
+				.unwrap_or_else(Zero::zero)
+		}
 
-			TotalRewards::<T>::mutate(|r| *r = r.saturating_add(amount));
+		/// Pay out the pending rewards of the position `id` to its owner `who`
+		fn settle_position(who: &T::AccountId, id: PositionId) -> DispatchResult {
+			let shares = Positions::<T>::get(id).map(|info| info.shares).unwrap_or_default();
+			Self::settle_asset_rewards(&Depositor::Position(id), who, shares, shares)?;
 
-			let total_deposited = Self::total_deposited();
-			if !total_deposited.is_zero() {
-				let precision = Self::precision();
-				let increment = amount.saturating_mul(precision) / total_deposited;
-				AccRewardPerShare::<T>::mutate(|v| *v = v.saturating_add(increment));
+			let pending = Self::pending_position_rewards(id);
+			if pending.is_zero() {
+				return Ok(());
 			}
+			ensure!(Self::pool_balance() >= pending, Error::<T>::InsufficientPoolBalance);
+			let mut pool = Self::native_pool();
+			Positions::<T>::try_mutate(id, |maybe_info| -> DispatchResult {
+				let info = maybe_info.as_mut().ok_or(Error::<T>::UnknownPosition)?;
+				pool.settle(info);
+				Ok(())
+			})?;
+			Self::put_native_pool(pool);
+			Self::pay_reward(who, pending)?;
 
+			T::OnPoolChange::on_claim(who, pending);
 			Ok(())
 		}
-	}
 
-	impl<T: Config> Pallet<T> {
-		/// The account ID of the pool
-		pub fn account_id() -> T::AccountId {
-			T::PalletId::get().into_account_truncating()
+		/// Ensure the pools are not paused
+		fn ensure_not_paused() -> DispatchResult {
+			ensure!(!Self::paused(), Error::<T>::Paused);
+			Ok(())
+		}
+
+		/// The amount `who` has deposited in the native pool, regular, in positions it owns and
+		/// compounding
+		pub(crate) fn deposited_by(who: &T::AccountId) -> BalanceOf<T> {
+			Deposits::<T>::get(who)
+				.map(|info| info.amount)
+				.unwrap_or_else(Zero::zero)
+				.saturating_add(Self::positions_balance(who))
+				.saturating_add(Self::compounding_balance(who))
+		}
+
+		/// The amount deposited in the positions owned by `who`
+		fn positions_balance(who: &T::AccountId) -> BalanceOf<T> {
+			let Some(collection) = Self::position_collection() else { return Zero::zero() };
+			T::Nfts::owned_in_collection(&collection, who)
+				.filter_map(Positions::<T>::get)
+				.fold(Zero::zero(), |total, info| total.saturating_add(info.amount))
+		}
+
+		/// Ensure depositing `amount` into the native pool, on top of `current` already
+		/// deposited by the same account, respects the deposit limits
+		fn ensure_deposit_allowed(current: BalanceOf<T>, amount: BalanceOf<T>) -> DispatchResult {
+			Self::check_deposit(&Self::deposit_limits(), Self::total_deposited(), current, amount)
+		}
+
+		/// Ensure adding `amount` to the native pool, on top of `current` already deposited by
+		/// the same account, stays within the account limit and the pool cap
+		fn ensure_within_limits(current: BalanceOf<T>, amount: BalanceOf<T>) -> DispatchResult {
+			Self::check_limits(&Self::deposit_limits(), Self::total_deposited(), current, amount)
+		}
+
+		/// The limits on deposits into the native pool
+		fn deposit_limits() -> DepositLimits<BalanceOf<T>> {
+			DepositLimits {
+				min_deposit: Self::min_deposit(),
+				max_per_account: Self::max_deposit_per_account(),
+				pool_cap: Self::pool_cap(),
+			}
+		}
+
+		/// Ensure depositing `amount` into a pool holding `total_deposited`, on top of
+		/// `current` already deposited by the same account, respects `limits`
+		fn check_deposit(
+			limits: &DepositLimits<BalanceOf<T>>,
+			total_deposited: BalanceOf<T>,
+			current: BalanceOf<T>,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			ensure!(amount >= limits.min_deposit, Error::<T>::DepositTooSmall);
+			Self::check_limits(limits, total_deposited, current, amount)
+		}
+
+		/// Ensure adding `amount` to a pool holding `total_deposited`, on top of `current`
+		/// already deposited by the same account, stays within the account limit and the pool
+		/// cap of `limits`
+		fn check_limits(
+			limits: &DepositLimits<BalanceOf<T>>,
+			total_deposited: BalanceOf<T>,
+			current: BalanceOf<T>,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			if let Some(max) = limits.max_per_account {
+				ensure!(current.saturating_add(amount) <= max, Error::<T>::AccountLimitExceeded);
+			}
+			if let Some(cap) = limits.pool_cap {
+				ensure!(
+					total_deposited.saturating_add(amount) <= cap,
+					Error::<T>::PoolCapExceeded
+				);
+			}
+			Ok(())
+		}
+
+		/// Hold the storage deposit for the new entry of `depositor` from `payer`
+		fn take_entry_deposit(
+			depositor: DepositorOf<T>,
+			payer: &T::AccountId,
+		) -> DispatchResult {
+			let amount = T::EntryDeposit::get();
+			if amount.is_zero() {
+				return Ok(());
+			}
+			T::Currency::hold(&HoldReason::StorageDeposit.into(), payer, amount)?;
+			EntryDeposits::<T>::insert(depositor, (payer.clone(), amount));
+			Ok(())
+		}
+
+		/// Release the storage deposit of the removed entry of `depositor` to its payer
+		fn refund_entry_deposit(depositor: &DepositorOf<T>) -> DispatchResult {
+			if let Some((payer, amount)) = EntryDeposits::<T>::take(depositor) {
+				T::Currency::release(
+					&HoldReason::StorageDeposit.into(),
+					&payer,
+					amount,
+					Precision::BestEffort,
+				)?;
+			}
+			Ok(())
+		}
+
+		/// The shares of the regular deposit of `who`
+		fn shares_of(who: &T::AccountId) -> BalanceOf<T> {
+			Deposits::<T>::get(who).map(|info| info.shares).unwrap_or_else(Zero::zero)
+		}
+
+		/// The pending rewards in each reward asset of `depositor` holding `shares`
+		fn pending_asset_rewards_of(
+			depositor: &DepositorOf<T>,
+			shares: BalanceOf<T>,
+		) -> Vec<(T::AssetId, BalanceOf<T>)> {
+			Self::reward_assets()
+				.into_iter()
+				.map(|asset| {
+					let total_due =
+						PoolState::accrued(Self::asset_acc_reward_per_share(&asset), shares);
+					let debt = AssetRewardDebts::<T>::get(depositor, &asset);
+					(asset, total_due.saturating_sub(debt))
+				})
+				.collect()
+		}
+
+		/// The pending rewards in each reward asset of the regular deposit of `who`
+		pub fn pending_asset_rewards(who: &T::AccountId) -> Vec<(T::AssetId, BalanceOf<T>)> {
+			Self::pending_asset_rewards_of(&Depositor::Account(who.clone()), Self::shares_of(who))
+		}
+
+		/// Pay the pending reward assets of `depositor`, holding `shares`, to `payee` and reset
+		/// its reward debts for holding `new_shares` from now on
+		fn settle_asset_rewards(
+			depositor: &DepositorOf<T>,
+			payee: &T::AccountId,
+			shares: BalanceOf<T>,
+			new_shares: BalanceOf<T>,
+		) -> DispatchResult {
+			for (asset, pending) in Self::pending_asset_rewards_of(depositor, shares) {
+				if !pending.is_zero() {
+					<T::Assets as fungibles::Mutate<_>>::transfer(
+						asset.clone(),
+						&Self::account_id(),
+						payee,
+						pending,
+						Preservation::Expendable,
+					)?;
+				}
+				let debt = PoolState::accrued(Self::asset_acc_reward_per_share(&asset), new_shares);
+				if debt.is_zero() {
+					AssetRewardDebts::<T>::remove(depositor, &asset);
+				} else {
+					AssetRewardDebts::<T>::insert(depositor, &asset, debt);
+				}
+			}
+			Ok(())
+		}
+
+		/// The receipt tokens held by `who`
+		pub fn receipt_balance(who: &T::AccountId) -> BalanceOf<T> {
+			<T::Assets as fungibles::Inspect<_>>::balance(T::ReceiptAssetId::get(), who)
+		}
+
+		/// Create the receipt asset, owned by the pool account, unless it exists
+		pub(crate) fn create_receipt_asset() -> DispatchResult {
+			let asset = T::ReceiptAssetId::get();
+			if <T::Assets as fungibles::Inspect<_>>::asset_exists(asset.clone()) {
+				return Ok(());
+			}
+			<T::Assets as fungibles::Create<_>>::create(asset, Self::account_id(), true, 1u32.into())
+		}
+
+		/// Mint `amount` of receipt tokens to `who`
+		fn mint_receipt(who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			<T::Assets as fungibles::Mutate<_>>::mint_into(T::ReceiptAssetId::get(), who, amount)?;
+			Ok(())
+		}
+
+		/// Burn `amount` of receipt tokens of `who`
+		fn burn_receipt(who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			if amount.is_zero() {
+				return Ok(());
+			}
+			<T::Assets as fungibles::Mutate<_>>::burn_from(
+				T::ReceiptAssetId::get(),
+				who,
+				amount,
+				Preservation::Expendable,
+				Precision::Exact,
+				Fortitude::Polite,
+			)?;
+			Ok(())
+		}
+
+		/// The account holding the deposit of the compounding vault
+		pub fn vault_account() -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating(b"compound")
+		}
+
+		/// Principal of the compounding vault
+		fn vault_balance() -> BalanceOf<T> {
+			Deposits::<T>::get(Self::vault_account()).map(|info| info.amount).unwrap_or_default()
+		}
+
+		/// The amount of tokens `who` owns in the compounding vault
+		pub fn compounding_balance(who: &T::AccountId) -> BalanceOf<T> {
+			match CompoundDeposits::<T>::get(who) {
+				Some(deposit) => {
+					let vault = Self::vault_account();
+					let pending = Self::calculate_pending_rewards(&vault).unwrap_or_default();
+					let vault_balance = Self::vault_balance().saturating_add(pending);
+					Self::vault_amount_for(deposit.shares, vault_balance)
+				},
+				None => Zero::zero(),
+			}
+		}
+
+		/// The vault shares worth `amount` of tokens, with the vault holding `vault_balance`
+		fn vault_shares_for(amount: BalanceOf<T>, vault_balance: BalanceOf<T>) -> BalanceOf<T> {
+			let virtual_shares = T::VaultVirtualShares::get();
+			let total_shares = Self::total_compound_shares().saturating_add(virtual_shares);
+			let vault_balance = vault_balance.saturating_add(virtual_shares);
+			if total_shares.is_zero() || vault_balance.is_zero() {
+				return amount;
+			}
+			amount.saturating_mul(total_shares) / vault_balance
+		}
+
+		/// The tokens `shares` vault shares are worth, with the vault holding `vault_balance`
+		fn vault_amount_for(shares: BalanceOf<T>, vault_balance: BalanceOf<T>) -> BalanceOf<T> {
+			let virtual_shares = T::VaultVirtualShares::get();
+			let total_shares = Self::total_compound_shares().saturating_add(virtual_shares);
+			if total_shares.is_zero() {
+				return Zero::zero();
+			}
+			shares.saturating_mul(vault_balance.saturating_add(virtual_shares)) / total_shares
+		}
+
+		/// The reward accounting of the native pool
+		pub(crate) fn native_pool() -> PoolState<BalanceOf<T>> {
+			PoolState {
+				total_deposited: Self::total_deposited(),
+				total_shares: Self::total_shares(),
+				total_rewards: Self::total_rewards(),
+				acc_reward_per_share: Self::acc_reward_per_share(),
+			}
+		}
+
+		/// Store the reward accounting of the native pool
+		fn put_native_pool(pool: PoolState<BalanceOf<T>>) {
+			TotalDeposited::<T>::put(pool.total_deposited);
+			TotalShares::<T>::put(pool.total_shares);
+			TotalRewards::<T>::put(pool.total_rewards);
+			AccRewardPerShare::<T>::put(pool.acc_reward_per_share);
+		}
+
+		/// Free balance of the pool account available for payouts
+		fn pool_balance() -> BalanceOf<T> {
+			T::Currency::reducible_balance(
+				&Self::account_id(),
+				Preservation::Expendable,
+				Fortitude::Polite,
+			)
+		}
+
+		/// Add `amount` to the deposit of `who` under the lock `term`, paying out its pending
+		/// rewards first
+		///
+		/// Deposits of the compounding vault are reported to `OnPoolChange` by the caller, for
+		/// the account depositing into the vault.
+		fn add_deposit(
+			who: &T::AccountId,
+			amount: BalanceOf<T>,
+			term: LockTerm<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			Self::settle_rewards(who)?;
+
+			let now = frame_system::Pallet::<T>::block_number();
+			let shares = amount.saturating_mul(term.reward_multiplier.into()) / 100u32.into();
+			let unlock_block = now.saturating_add(term.duration);
+
+			if who != &Self::vault_account() {
+				if !Deposits::<T>::contains_key(who) {
+					Self::take_entry_deposit(Depositor::Account(who.clone()), who)?;
+				}
+				let current = Self::shares_of(who);
+				Self::settle_asset_rewards(
+					&Depositor::Account(who.clone()),
+					who,
+					current,
+					current.saturating_add(shares),
+				)?;
+			}
+
+			let mut pool = Self::native_pool();
+			let mut info = Deposits::<T>::get(who).unwrap_or(DepositInfo {
+				amount: Zero::zero(),
+				deposit_block: now,
+				reward_debt: Zero::zero(),
+				shares: Zero::zero(),
+				unlock_block,
+			});
+			pool.stake(&mut info, amount, shares);
+			info.deposit_block = now;
+			info.unlock_block = info.unlock_block.max(unlock_block);
+			Deposits::<T>::insert(who, &info);
+			Self::put_native_pool(pool);
+
+			if info.shares > info.amount {
+				Self::schedule_boost_expiry(
+					Depositor::Account(who.clone()),
+					info.unlock_block.max(now.saturating_add(One::one())),
+				)?;
+			}
+			if who != &Self::vault_account() {
+				T::OnPoolChange::on_deposit(who, amount);
+			}
+			Ok(())
+		}
+
+		/// Queue the lock term multiplier of `depositor` to end at block `at`
+		fn schedule_boost_expiry(
+			depositor: DepositorOf<T>,
+			at: BlockNumberFor<T>,
+		) -> DispatchResult {
+			if !BoostExpiries::<T>::contains_key(at) {
+				BoostExpiryBlocks::<T>::try_mutate(|blocks| {
+					let index = blocks.binary_search(&at).unwrap_or_else(|index| index);
+					blocks.try_insert(index, at)
+				})
+				.map_err(|_| Error::<T>::TooManyBoostExpiries)?;
+			}
+			BoostExpiries::<T>::try_mutate(at, |queue| {
+				if queue.contains(&depositor) {
+					return Ok(());
+				}
+				queue.try_push(depositor)
+			})
+			.map_err(|_| Error::<T>::TooManyBoostExpiries.into())
+		}
+
+		/// End the lock term multipliers queued to end by block `now`, returning the weight used
+		///
+		/// Blocks are processed earliest first, stopping once `MaxBoostExpiriesPerBlock`
+		/// entries were processed, so that a backlog left by a pause is cleared over several
+		/// blocks.
+		fn expire_boosts(now: BlockNumberFor<T>) -> Weight {
+			let db = T::DbWeight::get();
+			let mut weight = db.reads(2);
+			let mut blocks = BoostExpiryBlocks::<T>::get();
+			let mut processed = 0u32;
+			let mut done = 0;
+			for &block in blocks.iter() {
+				if block > now || processed >= T::MaxBoostExpiriesPerBlock::get() {
+					break;
+				}
+				let queue = BoostExpiries::<T>::take(block);
+				weight.saturating_accrue(db.reads_writes(1, 1));
+				for depositor in queue {
+					// Settling pays out native and asset rewards, see `settle_rewards`.
+					weight.saturating_accrue(db.reads_writes(12, 10));
+					let result = with_storage_layer(|| Self::expire_boost(&depositor, block));
+					if let Err(e) = result {
+						log::warn!(
+							target: "runtime::native-pools",
+							"lock term multiplier not ended: {:?}",
+							e,
+						);
+					}
+					processed.saturating_inc();
+				}
+				done += 1;
+			}
+			if done > 0 {
+				blocks.drain(..done);
+				BoostExpiryBlocks::<T>::put(blocks);
+				weight.saturating_accrue(db.writes(1));
+			}
+			weight
+		}
+
+		/// Pay out the pending rewards of `depositor` and bring its reward weight back to its
+		/// amount, if its lock term still ends at block `at`
+		///
+		/// Entries of deposits that were withdrawn or locked again since are skipped.
+		fn expire_boost(
+			depositor: &DepositorOf<T>,
+			at: BlockNumberFor<T>,
+		) -> DispatchResult {
+			let (who, info) = match depositor {
+				Depositor::Account(who) => {
+					let Some(info) = Deposits::<T>::get(who) else { return Ok(()) };
+					(who.clone(), info)
+				},
+				Depositor::Position(id) => {
+					let Some(info) = Positions::<T>::get(id) else { return Ok(()) };
+					let collection =
+						Self::position_collection().ok_or(Error::<T>::UnknownPosition)?;
+					let owner =
+						T::Nfts::owner(&collection, id).ok_or(Error::<T>::UnknownPosition)?;
+					(owner, info)
+				},
+				Depositor::Token(token, who) => return Self::expire_token_boost(token, who, at),
+				// Compounding deposits have no lock term.
+				Depositor::Compounding(_) => return Ok(()),
+			};
+			if info.unlock_block > at || info.shares <= info.amount {
+				return Ok(());
+			}
+
+			match depositor {
+				Depositor::Account(who) => Self::settle_rewards(who)?,
+				Depositor::Position(id) => Self::settle_position(&who, *id)?,
+				Depositor::Token(..) | Depositor::Compounding(_) => {},
+			}
+			Self::settle_asset_rewards(depositor, &who, info.shares, info.amount)?;
+			// The rewards were just settled, so none are pending.
+			let mut pool = Self::native_pool();
+			let update = |info: &mut DepositInfo<BalanceOf<T>, BlockNumberFor<T>>| {
+				let amount = info.amount;
+				pool.reweight(info, amount);
+			};
+			match depositor {
+				Depositor::Account(who) => Deposits::<T>::mutate(who, |i| i.as_mut().map(update)),
+				Depositor::Position(id) => Positions::<T>::mutate(id, |i| i.as_mut().map(update)),
+				Depositor::Token(..) | Depositor::Compounding(_) => None,
+			};
+			Self::put_native_pool(pool);
+			Ok(())
+		}
+
+		/// Pay out the pending rewards of the deposit of `who` in the token pool of `token` and
+		/// bring its reward weight back to its amount, if its lock term still ends at block `at`
+		fn expire_token_boost(
+			token: &T::StakingToken,
+			who: &T::AccountId,
+			at: BlockNumberFor<T>,
+		) -> DispatchResult {
+			let Some(info) = TokenDeposits::<T>::get(token, who) else { return Ok(()) };
+			if info.unlock_block > at || info.shares <= info.amount {
+				return Ok(());
+			}
+			Self::settle_token_rewards(token, who)?;
+
+			let mut pool = TokenPools::<T>::get(token).ok_or(Error::<T>::UnknownTokenPool)?;
+			let mut info = TokenDeposits::<T>::get(token, who).ok_or(Error::<T>::NoDeposit)?;
+			let amount = info.amount;
+			pool.reweight(&mut info, amount);
+			TokenDeposits::<T>::insert(token, who, info);
+			TokenPools::<T>::insert(token, pool);
+			Ok(())
+		}
+
+		/// Pay `amount` of native rewards of the regular deposit of `who` to its reward
+		/// destination
+		///
+		/// Rewards are paid to `who` instead of being compounded if it has no deposit left or
+		/// the deposit limits leave no room for them.
+		fn pay_depositor_reward(who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			if amount.is_zero() {
+				return Ok(());
+			}
+			let dest = Self::reward_destination(who);
+			let compounded = dest == RewardDestination::Compound &&
+				Deposits::<T>::contains_key(who) &&
+				Self::ensure_within_limits(Self::deposited_by(who), amount).is_ok();
+			match dest {
+				_ if compounded => Self::compound_reward(who, amount)?,
+				RewardDestination::Account(dest) => Self::pay_reward(&dest, amount)?,
+				_ => Self::pay_reward(who, amount)?,
+			}
+
+			T::OnPoolChange::on_claim(who, amount);
+			if compounded {
+				T::OnPoolChange::on_deposit(who, amount);
+			}
+			Ok(())
+		}
+
+		/// Add `amount` of rewards, already held by the pool, to the regular deposit of `who`
+		/// with one share per token
+		fn compound_reward(who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			let mut info = Deposits::<T>::get(who).ok_or(Error::<T>::NoDeposit)?;
+			Self::settle_asset_rewards(
+				&Depositor::Account(who.clone()),
+				who,
+				info.shares,
+				info.shares.saturating_add(amount),
+			)?;
+			let mut pool = Self::native_pool();
+			pool.stake(&mut info, amount, amount);
+			Deposits::<T>::insert(who, &info);
+			Self::put_native_pool(pool);
+			Self::mint_receipt(who, amount)
+		}
+
+		/// Pay `amount` of rewards from the pool to `who`, vesting them if a curve is set
+		fn pay_reward(who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			Self::pay_reward_from(&Self::account_id(), who, amount)
+		}
+
+		/// Pay `amount` of native rewards from `source` to `who`, vesting them if a curve is set
+		///
+		/// Rewards that cannot be vested, for example because the vesting of `who` is frozen
+		/// or its schedules are full, are held in the pool account until `who` vests them with
+		/// `vest_held_rewards`, so that they never block the withdrawal they are paid with.
+		fn pay_reward_from(
+			source: &T::AccountId,
+			who: &T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			if amount.is_zero() {
+				return Ok(());
+			}
+			let Some(curve) = Self::reward_vesting_curve() else {
+				return T::Currency::transfer(source, who, amount, Preservation::Expendable)
+					.map(|_| ());
+			};
+			let vested = with_storage_layer(|| {
+				T::RewardVesting::vest_reward(source, who, amount, curve.period, curve.period_count)
+			});
+			if vested.is_err() {
+				let pool_account = Self::account_id();
+				if *source != pool_account {
+					T::Currency::transfer(source, &pool_account, amount, Preservation::Expendable)?;
+				}
+				HeldRewards::<T>::mutate(who, |held| held.saturating_accrue(amount));
+			}
+			Ok(())
+		}
+
+		/// The account holding the staking tokens and rewards of the pool for `token`
+		pub fn token_pool_account(token: &T::StakingToken) -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating((b"token", token))
+		}
+
+		/// The pending rewards of `who` in the pool for `token`
+		pub fn pending_token_rewards(token: &T::StakingToken, who: &T::AccountId) -> BalanceOf<T> {
+			match (TokenPools::<T>::get(token), TokenDeposits::<T>::get(token, who)) {
+				(Some(pool), Some(info)) => pool.pending(&info),
+				_ => Zero::zero(),
+			}
+		}
+
+		/// Add `amount` of `token` to the deposit of `who` in its token pool under the lock
+		/// `term`, paying out its pending rewards first
+		fn add_token_deposit(
+			who: &T::AccountId,
+			token: T::StakingToken,
+			amount: BalanceOf<T>,
+			term: LockTerm<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			Self::ensure_not_paused()?;
+			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+			let mut pool = TokenPools::<T>::get(&token).ok_or(Error::<T>::UnknownTokenPool)?;
+			let current = TokenDeposits::<T>::get(&token, who);
+			Self::check_deposit(
+				&Self::token_pool_limits(&token),
+				pool.total_deposited,
+				current.as_ref().map(|info| info.amount).unwrap_or_else(Zero::zero),
+				amount,
+			)?;
+
+			let pool_account = Self::token_pool_account(&token);
+			<T::StakingAssets as fungibles::Mutate<_>>::transfer(
+				token.clone(),
+				who,
+				&pool_account,
+				amount,
+				Preservation::Expendable,
+			)?;
+			if current.is_none() {
+				Self::take_entry_deposit(Depositor::Token(token.clone(), who.clone()), who)?;
+			}
+
+			let now = frame_system::Pallet::<T>::block_number();
+			let shares = amount.saturating_mul(term.reward_multiplier.into()) / 100u32.into();
+			let unlock_block = now.saturating_add(term.duration);
+			let mut info = current.unwrap_or(DepositInfo {
+				amount: Zero::zero(),
+				deposit_block: now,
+				reward_debt: Zero::zero(),
+				shares: Zero::zero(),
+				unlock_block,
+			});
+			let pending = pool.stake(&mut info, amount, shares);
+			info.deposit_block = now;
+			info.unlock_block = info.unlock_block.max(unlock_block);
+			TokenDeposits::<T>::insert(&token, who, &info);
+			TokenPools::<T>::insert(&token, pool);
+			Self::pay_reward_from(&pool_account, who, pending)?;
+
+			if info.shares > info.amount {
+				Self::schedule_boost_expiry(
+					Depositor::Token(token.clone(), who.clone()),
+					info.unlock_block.max(now.saturating_add(One::one())),
+				)?;
+			}
+			if !pending.is_zero() {
+				T::OnPoolChange::on_token_claim(&token, who, pending);
+			}
+			T::OnPoolChange::on_token_deposit(&token, who, amount);
+			Ok(())
+		}
+
+		/// Pay out the pending rewards of `who` in the token pool of `token`, if it has a
+		/// deposit, returning them
+		fn settle_token_rewards(
+			token: &T::StakingToken,
+			who: &T::AccountId,
+		) -> Result<BalanceOf<T>, DispatchError> {
+			let Some(mut info) = TokenDeposits::<T>::get(token, who) else {
+				return Ok(Zero::zero())
+			};
+			let mut pool = TokenPools::<T>::get(token).ok_or(Error::<T>::UnknownTokenPool)?;
+			let pending = pool.settle(&mut info);
+			if pending.is_zero() {
+				return Ok(pending);
+			}
+			TokenDeposits::<T>::insert(token, who, info);
+			TokenPools::<T>::insert(token, pool);
+			Self::pay_reward_from(&Self::token_pool_account(token), who, pending)?;
+
+			T::OnPoolChange::on_token_claim(token, who, pending);
+			Ok(pending)
+		}
+
+		/// The exit fee `who` would pay to withdraw `amount` of pooled tokens now
+		pub fn exit_fee(who: &T::AccountId, amount: BalanceOf<T>) -> BalanceOf<T> {
+			Deposits::<T>::get(who)
+				.map(|info| Self::exit_fee_at(info.deposit_block, amount))
+				.unwrap_or_else(Zero::zero)
+		}
+
+		/// The exit fee on withdrawing `amount` of tokens deposited at `deposit_block` now
+		fn exit_fee_at(deposit_block: BlockNumberFor<T>, amount: BalanceOf<T>) -> BalanceOf<T> {
+			let Some(fee) = Self::exit_fee_schedule() else { return Zero::zero() };
+			let age = frame_system::Pallet::<T>::block_number().saturating_sub(deposit_block);
+			if age >= fee.decay_period {
+				return Zero::zero();
+			}
+			let remaining = Perbill::from_rational(
+				fee.decay_period.saturating_sub(age).saturated_into::<u128>(),
+				fee.decay_period.saturated_into::<u128>(),
+			);
+			(fee.initial * remaining).mul_floor(amount)
+		}
+
+		/// Send the treasury share of an exit `fee` to the treasury and add the rest to the
+		/// rewards of the remaining depositors, returning the amount actually charged
+		///
+		/// Without remaining depositors, only the treasury share is charged.
+		fn distribute_exit_fee(fee: BalanceOf<T>) -> Result<BalanceOf<T>, DispatchError> {
+			let Some(schedule) = Self::exit_fee_schedule() else { return Ok(Zero::zero()) };
+			if fee.is_zero() {
+				return Ok(Zero::zero());
+			}
+			let to_treasury = schedule.treasury_share.mul_floor(fee);
+			let mut redistributed = fee.saturating_sub(to_treasury);
+
+			if Self::total_shares().is_zero() {
+				redistributed = Zero::zero();
+			} else {
+				Self::distribute_rewards(redistributed);
+			}
+
+			if !to_treasury.is_zero() {
+				T::Currency::transfer(
+					&Self::account_id(),
+					&T::TreasuryAccount::get(),
+					to_treasury,
+					Preservation::Expendable,
+				)?;
+			}
+			Ok(to_treasury.saturating_add(redistributed))
+		}
+
+		/// Queue `amount` of withdrawn principal of `who` for the unbonding period
+		fn unbond(who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			if amount.is_zero() {
+				return Ok(());
+			}
+			let unlock_block =
+				frame_system::Pallet::<T>::block_number().saturating_add(T::UnbondingPeriod::get());
+			Unbonding::<T>::try_mutate(who, |chunks| {
+				match chunks.last_mut() {
+					Some(last) if last.unlock_block == unlock_block => {
+						last.value = last.value.saturating_add(amount);
+						Ok(())
+					},
+					_ => chunks
+						.try_push(UnlockChunk { value: amount, unlock_block })
+						.map_err(|_| Error::<T>::TooManyUnlockingChunks.into()),
+				}
+			})
+		}
+
+		/// Move `amount` of native rewards from `source` into the pool
+		fn do_deposit_rewards(source: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			Self::ensure_not_paused()?;
+			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+
+			let pool_account = Self::account_id();
+			T::Currency::transfer(source, &pool_account, amount, Preservation::Expendable)?;
+
+			Self::distribute_rewards(amount);
+
+			Self::update_pool()?;
+			T::OnPoolChange::on_rewards_deposited(amount);
+			Ok(())
+		}
+
+		/// Share out `amount` of native rewards, already held by the pool account, over the
+		/// native pool and record them in the reward snapshot of the day
+		fn distribute_rewards(amount: BalanceOf<T>) {
+			let mut pool = Self::native_pool();
+			pool.distribute(amount);
+			Self::put_native_pool(pool);
+			Self::record_snapshot(amount);
+		}
+
+		/// The index of the current day, counted in `BlocksPerDay` blocks from genesis
+		pub fn current_day() -> u32 {
+			Self::day_of(frame_system::Pallet::<T>::block_number())
+		}
+
+		/// The index of the day of `block`, counted in `BlocksPerDay` blocks from genesis
+		fn day_of(block: BlockNumberFor<T>) -> u32 {
+			let blocks_per_day = T::BlocksPerDay::get();
+			if blocks_per_day.is_zero() {
+				return 0;
+			}
+			(block / blocks_per_day).saturated_into()
+		}
+
+		/// Submit the scheduled reward distribution of the current day, unless it was already
+		/// distributed or submitted by this node
+		///
+		/// The last submitted day is claimed in offchain storage before submitting, so that
+		/// concurrent workers, such as those of sibling blocks, submit only once.
+		fn run_keeper(now: BlockNumberFor<T>) -> Result<(), &'static str> {
+			if Self::scheduled_reward().is_none() || Self::paused() {
+				return Ok(());
+			}
+			let blocks_per_day = T::BlocksPerDay::get();
+			if blocks_per_day.is_zero() {
+				return Ok(());
+			}
+			let day: u32 = (now / blocks_per_day).saturated_into();
+			if Self::last_reward_day().is_some_and(|last| last >= day) {
+				return Ok(());
+			}
+
+			let signer = Signer::<T, T::KeeperAuthorityId>::any_account();
+			if !signer.can_sign() {
+				return Err("no keeper key in the keystore");
+			}
+
+			let mut storage = StorageValueRef::persistent(KEEPER_LAST_DAY);
+			let claimed = storage.mutate(|last: Result<Option<u32>, StorageRetrievalError>| {
+				match last {
+					Ok(Some(last)) if last >= day => Err("already submitted"),
+					_ => Ok(day),
+				}
+			});
+			match claimed {
+				Ok(_) => {},
+				Err(MutateStorageError::ValueFunctionFailed(_)) => return Ok(()),
+				Err(MutateStorageError::ConcurrentModification(_)) =>
+					return Err("concurrent keeper submission"),
+			}
+
+			match signer.send_signed_transaction(|_| Call::<T>::distribute_scheduled_reward { day }) {
+				Some((_, Ok(()))) => Ok(()),
+				_ => {
+					// Let the next block retry.
+					storage.clear();
+					Err("failed to submit the reward distribution")
+				},
+			}
+		}
+
+		/// Add `rewards` shared out over the native pool to the snapshot of the current day,
+		/// starting a new snapshot on the first rewards of the day and overwriting the oldest one
+		/// once the ring buffer is full
+		fn record_snapshot(rewards: BalanceOf<T>) {
+			let max = T::MaxRewardSnapshots::get();
+			if max == 0 {
+				return;
+			}
+			let now = frame_system::Pallet::<T>::block_number();
+			let snapshot = RewardSnapshot {
+				block: now,
+				rewards,
+				total_deposited: Self::total_deposited(),
+				acc_reward_per_share: Self::acc_reward_per_share(),
+			};
+			let index = Self::next_reward_snapshot();
+			let latest = index.checked_sub(1).unwrap_or(max - 1);
+			match RewardSnapshots::<T>::get(latest) {
+				Some(last) if Self::day_of(last.block) == Self::day_of(now) => {
+					let rewards = last.rewards.saturating_add(rewards);
+					RewardSnapshots::<T>::insert(latest, RewardSnapshot { rewards, ..snapshot });
+				},
+				_ => {
+					RewardSnapshots::<T>::insert(index, snapshot);
+					NextRewardSnapshot::<T>::put(index.saturating_add(1) % max);
+				},
+			}
 		}
 
-		/// Precision factor for reward calculations (1e12)
-		fn precision() -> BalanceOf<T> {
-			(1_000_000_000_000u128).saturated_into()
+		/// The annual percentage rate of the native pool over the trailing `days` days, as a
+		/// fraction of the deposited tokens
+		///
+		/// Each day in the window adds its rewards relative to the tokens deposited after its
+		/// latest rewards, and the sum is scaled to a year. Days older than the snapshots kept
+		/// in `RewardSnapshots` are not counted.
+		pub fn trailing_apr(days: u32) -> FixedU128 {
+			if days == 0 {
+				return FixedU128::zero();
+			}
+			let window = T::BlocksPerDay::get().saturating_mul(days.into());
+			let from = frame_system::Pallet::<T>::block_number().saturating_sub(window);
+			let rate = RewardSnapshots::<T>::iter_values()
+				.filter(|snapshot| snapshot.block > from && !snapshot.total_deposited.is_zero())
+				.fold(FixedU128::zero(), |rate, snapshot| {
+					rate.saturating_add(FixedU128::saturating_from_rational(
+						snapshot.rewards.saturated_into::<u128>(),
+						snapshot.total_deposited.saturated_into::<u128>(),
+					))
+				});
+			rate.saturating_mul(FixedU128::saturating_from_rational(365u32, days))
 		}
 
 		/// Update pool state (called before any state-changing operation)
 		fn update_pool() -> DispatchResult {
 			let now = frame_system::Pallet::<T>::block_number();
 			LastRewardBlock::<T>::put(now);
+			Self::compound();
 			Ok(())
 		}
+
+		/// Fold the pending rewards of the compounding vault into its principal
+		///
+		/// The rewards already are in the pool account, so no funds move. Nothing is compounded
+		/// while the pool is paused.
+		fn compound() {
+			if Self::paused() {
+				return;
+			}
+			let vault = Self::vault_account();
+			let Some(mut info) = Deposits::<T>::get(&vault) else { return };
+			let pending = Self::calculate_pending_rewards(&vault).unwrap_or_default();
+			if pending.is_zero() {
+				return;
+			}
+			let mut pool = Self::native_pool();
+			pool.stake(&mut info, pending, pending);
+			Deposits::<T>::insert(&vault, info);
+			Self::put_native_pool(pool);
+		}
+
 		/// Calculate pending rewards for a user
-		fn calculate_pending_rewards(who: &T::AccountId) -> Result<BalanceOf<T>, DispatchError> {
+		pub(crate) fn calculate_pending_rewards(who: &T::AccountId) -> Result<BalanceOf<T>, DispatchError> {
 			if let Some(info) = Deposits::<T>::get(who) {
-				let acc_per_share = Self::acc_reward_per_share();
-				let precision = Self::precision();
-				let total_due = acc_per_share.saturating_mul(info.amount) / precision;
-				let pending = total_due.saturating_sub(info.reward_debt);
-				Ok(pending)
+				Ok(Self::native_pool().pending(&info))
 			} else {
 				Err(Error::<T>::NoDeposit.into())
 			}
 		}
 
 	}
-}
\ No newline at end of file
+}
+
+/// Accepts signed origins of the members of the reward team, resolving to their account
+///
+/// Meant to be used as `Config::RewardOrigin`, possibly combined with other origins.
+pub struct EnsureRewardTeam<T>(PhantomData<T>);
+
+impl<T: Config> EnsureOrigin<OriginFor<T>> for EnsureRewardTeam<T> {
+	type Success = T::AccountId;
+
+	fn try_origin(o: OriginFor<T>) -> Result<T::AccountId, OriginFor<T>> {
+		match o.clone().into() {
+			Ok(frame_system::RawOrigin::Signed(who)) if RewardTeam::<T>::contains_key(&who) =>
+				Ok(who),
+			_ => Err(o),
+		}
+	}
+}
+
+impl<T: Config> PoolInspect<T::AccountId> for Pallet<T> {
+	type Balance = BalanceOf<T>;
+
+	fn stake_of(who: &T::AccountId) -> BalanceOf<T> {
+		Self::deposited_by(who)
+	}
+
+	fn total_stake() -> BalanceOf<T> {
+		Self::total_deposited()
+	}
+}
@@ -0,0 +1,107 @@
+//! Storage migrations for the native pools pallet.
+
+use super::*;
+use core::marker::PhantomData;
+use polkadot_sdk::frame_support::{
+	migrations::VersionedMigration, traits::UncheckedOnRuntimeUpgrade, weights::Weight,
+};
+
+/// A deposit as stored before deposits had lock terms.
+#[derive(Clone, Encode, Decode, MaxEncodedLen, TypeInfo, Debug, PartialEq)]
+pub struct OldDepositInfo<Balance, BlockNumber> {
+	pub amount: Balance,
+	pub deposit_block: BlockNumber,
+	pub reward_debt: Balance,
+}
+
+/// Migration that adds reward shares and unlock blocks to deposits.
+pub mod v1 {
+	use super::*;
+
+	/// Gives every existing deposit as many shares as it has principal, unlocked from the
+	/// block it was made, and initialises `TotalShares` from `TotalDeposited`.
+	///
+	/// Since every share is worth one unit of principal, the reward debts stay valid.
+	pub struct InnerMigrateV0ToV1<T>(PhantomData<T>);
+
+	impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV0ToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let mut migrated: u64 = 0;
+			Deposits::<T>::translate::<OldDepositInfo<BalanceOf<T>, BlockNumberFor<T>>, _>(
+				|_, old| {
+					migrated.saturating_inc();
+					Some(DepositInfo {
+						amount: old.amount,
+						deposit_block: old.deposit_block,
+						reward_debt: old.reward_debt,
+						shares: old.amount,
+						unlock_block: old.deposit_block,
+					})
+				},
+			);
+			TotalShares::<T>::put(TotalDeposited::<T>::get());
+			T::DbWeight::get().reads_writes(migrated.saturating_add(1), migrated.saturating_add(1))
+		}
+	}
+
+	/// [`InnerMigrateV0ToV1`] wrapped in a [`VersionedMigration`], which ensures it runs only
+	/// once and bumps the storage version to 1.
+	pub type MigrateV0ToV1<T> = VersionedMigration<
+		0,
+		1,
+		InnerMigrateV0ToV1<T>,
+		Pallet<T>,
+		<T as frame_system::Config>::DbWeight,
+	>;
+}
+
+/// Migration that creates the receipt asset and backs every pooled token with a receipt.
+pub mod v2 {
+	use super::*;
+
+	/// Creates the receipt asset, unless it was already created on first use, and mints the
+	/// receipt tokens missing for the pooled tokens of each regular deposit.
+	pub struct InnerMigrateV1ToV2<T>(PhantomData<T>);
+
+	impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV1ToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if let Err(e) = Pallet::<T>::create_receipt_asset() {
+				log::error!(target: "runtime::native-pools", "receipt asset not created: {:?}", e);
+				return T::DbWeight::get().reads(1);
+			}
+			let vault = Pallet::<T>::vault_account();
+			let mut migrated: u64 = 0;
+			for (who, info) in Deposits::<T>::iter() {
+				migrated.saturating_inc();
+				if who == vault {
+					continue;
+				}
+				let pooled = info.amount.saturating_sub(LockedDeposits::<T>::get(&who));
+				let missing = pooled.saturating_sub(Pallet::<T>::receipt_balance(&who));
+				if missing.is_zero() {
+					continue;
+				}
+				let minted = <T::Assets as fungibles::Mutate<_>>::mint_into(
+					T::ReceiptAssetId::get(),
+					&who,
+					missing,
+				);
+				if let Err(e) = minted {
+					log::error!(target: "runtime::native-pools", "receipts not minted: {:?}", e);
+				}
+			}
+			let accesses = migrated.saturating_mul(3).saturating_add(2);
+			T::DbWeight::get().reads_writes(accesses, accesses)
+		}
+	}
+
+	/// [`InnerMigrateV1ToV2`] wrapped in a [`VersionedMigration`], which ensures it runs only
+	/// once and bumps the storage version to 2.
+	pub type MigrateV1ToV2<T> = VersionedMigration<
+		1,
+		2,
+		InnerMigrateV1ToV2<T>,
+		Pallet<T>,
+		<T as frame_system::Config>::DbWeight,
+	>;
+}
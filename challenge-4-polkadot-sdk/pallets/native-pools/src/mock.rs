@@ -4,22 +4,67 @@ use super::*;
 use polkadot_sdk::{
 	frame_support::{
 		construct_runtime, derive_impl, parameter_types,
-		traits::{ConstU32, ConstU64},
+		traits::{
+			fungible::{NativeFromLeft, NativeOrWithId, UnionOf},
+			AsEnsureOriginWithArg, ConstU32, ConstU64, EitherOf,
+		},
 		PalletId,
 	},
-	sp_runtime::{traits::IdentityLookup, BuildStorage, ConvertInto},
+	sp_runtime::{
+		testing::{TestSignature, UintAuthorityId},
+		traits::IdentityLookup,
+		BuildStorage,
+	},
+	*,
 };
 
 use crate as native_pools;
 
 /// Alias types for the mock runtime.
-pub type AccountId = u128;
+pub type AccountId = u64;
 type Balance = u64;
 type Block = frame_system::mocking::MockBlock<Runtime>;
 
 /// Pallet identifier used by the NativePools pallet in tests.
 parameter_types! {
 	pub const NativePoolsPalletId: PalletId = PalletId(*b"py/natpl");
+	pub const VestingFreezeId: [u8; 8] = *b"vesting ";
+	pub const RewardAccount: AccountId = TEAM;
+	pub const TreasuryAccount: AccountId = TREASURY;
+	pub const RewardReserve: AccountId = RESERVE;
+	pub static UnbondingPeriod: u64 = 0;
+	pub static EntryDeposit: Balance = 0;
+	pub static VaultVirtualShares: Balance = 0;
+	pub NftFeatures: pallet_nfts::PalletFeatures = pallet_nfts::PalletFeatures::all_enabled();
+	pub LockTerms: Vec<LockTerm<u64>> = vec![
+		LockTerm { duration: 0, reward_multiplier: 100 },
+		LockTerm { duration: 10, reward_multiplier: 150 },
+		LockTerm { duration: 20, reward_multiplier: 200 },
+	];
+	/// Rewards paid through `RewardVesting` as (account, amount, period, period count).
+	pub static VestedRewards: Vec<(AccountId, Balance, u64, u32)> = vec![];
+	/// Makes `RewardVesting` fail, as it does for accounts that cannot take a schedule.
+	pub static VestingFails: bool = false;
+	/// Changes reported to `OnPoolChange`.
+	pub static PoolChanges: Vec<PoolChange> = vec![];
+}
+
+/// Records vested rewards instead of creating vesting schedules.
+pub struct MockRewardVesting;
+
+impl RewardVesting<AccountId, Balance, u64> for MockRewardVesting {
+	fn vest_reward(
+		source: &AccountId,
+		dest: &AccountId,
+		amount: Balance,
+		period: u64,
+		period_count: u32,
+	) -> DispatchResult {
+		<PalletBalances as Mutate<_>>::transfer(source, dest, amount, Preservation::Expendable)?;
+		ensure!(!VestingFails::get(), DispatchError::Other("Cannot vest"));
+		VestedRewards::mutate(|v| v.push((*dest, amount, period, period_count)));
+		Ok(())
+	}
 }
 
 /// --- System configuration ---
@@ -45,15 +90,171 @@ impl pallet_balances::Config for Runtime {
 	type RuntimeHoldReason = RuntimeHoldReason;
 	type RuntimeFreezeReason = RuntimeFreezeReason;
 	type FreezeIdentifier = [u8; 8];
-	type MaxFreezes = ();
+	type MaxFreezes = ConstU32<1>;
 	type DoneSlashHandler = ();
 }
 
+/// --- Assets configuration ---
+#[derive_impl(pallet_assets::config_preludes::TestDefaultConfig)]
+impl pallet_assets::Config for Runtime {
+	type Currency = PalletBalances;
+	type CreateOrigin = AsEnsureOriginWithArg<frame_system::EnsureSigned<AccountId>>;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type Freezer = ();
+}
+
+/// --- Nfts configuration ---
+impl pallet_nfts::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type CollectionId = u32;
+	type ItemId = u32;
+	type Currency = PalletBalances;
+	type CreateOrigin = AsEnsureOriginWithArg<frame_system::EnsureSigned<AccountId>>;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type Locker = ();
+	type CollectionDeposit = ConstU64<0>;
+	type ItemDeposit = ConstU64<0>;
+	type MetadataDepositBase = ConstU64<0>;
+	type AttributeDepositBase = ConstU64<0>;
+	type DepositPerByte = ConstU64<0>;
+	type StringLimit = ConstU32<50>;
+	type KeyLimit = ConstU32<50>;
+	type ValueLimit = ConstU32<50>;
+	type ApprovalsLimit = ConstU32<10>;
+	type ItemAttributesApprovalsLimit = ConstU32<2>;
+	type MaxTips = ConstU32<10>;
+	type MaxDeadlineDuration = ConstU64<10_000>;
+	type MaxAttributesPerCall = ConstU32<2>;
+	type Features = NftFeatures;
+	type OffchainSignature = TestSignature;
+	type OffchainPublic = UintAuthorityId;
+	type WeightInfo = ();
+	type BlockNumberProvider = frame_system::Pallet<Runtime>;
+}
+
 /// --- NativePools configuration ---
 impl Config for Runtime {
+	type RuntimeHoldReason = RuntimeHoldReason;
 	type Currency = PalletBalances;
+	type VestingFreezeId = VestingFreezeId;
 	type PalletId = NativePoolsPalletId;
-	type RewardOrigin = frame_system::EnsureRoot<AccountId>;
+	type RewardOrigin = EitherOf<
+		frame_system::EnsureRootWithSuccess<AccountId, RewardAccount>,
+		EnsureRewardTeam<Runtime>,
+	>;
+	type RewardVesting = MockRewardVesting;
+	type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+	type LockTerms = LockTerms;
+	type MaxBoostExpiriesPerBlock = ConstU32<2>;
+	type MaxBoostExpiryBlocks = ConstU32<4>;
+	type UnbondingPeriod = UnbondingPeriod;
+	type MaxUnlockingChunks = ConstU32<2>;
+	type TreasuryAccount = TreasuryAccount;
+	type AssetId = u32;
+	type Assets = Assets;
+	type ReceiptAssetId = ConstU32<RECEIPT>;
+	type CollectionId = u32;
+	type Nfts = Nfts;
+	type NftCollectionConfig = pallet_nfts::CollectionConfigFor<Runtime>;
+	type NftItemConfig = pallet_nfts::ItemConfig;
+	type MaxRewardAssets = ConstU32<1>;
+	type EntryDeposit = EntryDeposit;
+	type VaultVirtualShares = VaultVirtualShares;
+	type StakingToken = NativeOrWithId<u32>;
+	type StakingAssets = UnionOf<PalletBalances, Assets, NativeFromLeft, NativeOrWithId<u32>, AccountId>;
+	type MaxRewardSnapshots = ConstU32<3>;
+	type BlocksPerDay = ConstU64<10>;
+	type RewardReserve = RewardReserve;
+	type KeeperAuthorityId = TestKeeperId;
+	type OnPoolChange = RecordPoolChanges;
+}
+
+/// A change of a pool, as reported to `OnPoolChange`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PoolChange {
+	Deposit(AccountId, Balance),
+	Withdraw(AccountId, Balance),
+	Claim(AccountId, Balance),
+	Rewards(Balance),
+	TokenDeposit(NativeOrWithId<u32>, AccountId, Balance),
+	TokenWithdraw(NativeOrWithId<u32>, AccountId, Balance),
+	TokenClaim(NativeOrWithId<u32>, AccountId, Balance),
+	TokenRewards(NativeOrWithId<u32>, Balance),
+}
+
+/// Records the pool changes in `PoolChanges`.
+pub struct RecordPoolChanges;
+
+impl OnPoolChange<AccountId, Balance, NativeOrWithId<u32>> for RecordPoolChanges {
+	fn on_deposit(who: &AccountId, amount: Balance) {
+		PoolChanges::mutate(|c| c.push(PoolChange::Deposit(*who, amount)));
+	}
+
+	fn on_withdraw(who: &AccountId, amount: Balance) {
+		PoolChanges::mutate(|c| c.push(PoolChange::Withdraw(*who, amount)));
+	}
+
+	fn on_claim(who: &AccountId, amount: Balance) {
+		PoolChanges::mutate(|c| c.push(PoolChange::Claim(*who, amount)));
+	}
+
+	fn on_rewards_deposited(amount: Balance) {
+		PoolChanges::mutate(|c| c.push(PoolChange::Rewards(amount)));
+	}
+
+	fn on_token_deposit(token: &NativeOrWithId<u32>, who: &AccountId, amount: Balance) {
+		PoolChanges::mutate(|c| c.push(PoolChange::TokenDeposit(token.clone(), *who, amount)));
+	}
+
+	fn on_token_withdraw(token: &NativeOrWithId<u32>, who: &AccountId, amount: Balance) {
+		PoolChanges::mutate(|c| c.push(PoolChange::TokenWithdraw(token.clone(), *who, amount)));
+	}
+
+	fn on_token_claim(token: &NativeOrWithId<u32>, who: &AccountId, amount: Balance) {
+		PoolChanges::mutate(|c| c.push(PoolChange::TokenClaim(token.clone(), *who, amount)));
+	}
+
+	fn on_token_rewards_deposited(token: &NativeOrWithId<u32>, amount: Balance) {
+		PoolChanges::mutate(|c| c.push(PoolChange::TokenRewards(token.clone(), amount)));
+	}
+}
+
+/// Keeper keys backed by the test keys of `UintAuthorityId`.
+pub struct TestKeeperId;
+
+impl AppCrypto<UintAuthorityId, TestSignature> for TestKeeperId {
+	type RuntimeAppPublic = UintAuthorityId;
+	type GenericPublic = UintAuthorityId;
+	type GenericSignature = TestSignature;
+}
+
+pub type Extrinsic = frame_system::mocking::MockUncheckedExtrinsic<Runtime>;
+
+impl frame_system::offchain::SigningTypes for Runtime {
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
+}
+
+impl<LocalCall> frame_system::offchain::CreateTransactionBase<LocalCall> for Runtime
+where
+	RuntimeCall: From<LocalCall>,
+{
+	type RuntimeCall = RuntimeCall;
+	type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Runtime
+where
+	RuntimeCall: From<LocalCall>,
+{
+	fn create_signed_transaction<C: AppCrypto<Self::Public, Self::Signature>>(
+		call: RuntimeCall,
+		_public: UintAuthorityId,
+		account: AccountId,
+		_nonce: u32,
+	) -> Option<Extrinsic> {
+		Some(Extrinsic::new_signed(call, account, (), ()))
+	}
 }
 
 /// --- Construct the runtime ---
@@ -62,16 +263,30 @@ construct_runtime!(
 		System: frame_system,
 		NativePools: native_pools,
 		PalletBalances: pallet_balances,
+		Assets: pallet_assets,
+		Nfts: pallet_nfts,
 	}
 );
 
 /// Predefined accounts used in the tests.
 pub const ALICE: AccountId = 1;
 pub const BOB: AccountId = 2;
+/// Team account that pays the rewards deposited by root.
+pub const TEAM: AccountId = 3;
+/// Treasury receiving a share of exit fees.
+pub const TREASURY: AccountId = 4;
+/// Reserve funding the scheduled rewards.
+pub const RESERVE: AccountId = 5;
+
+/// Receipt token minted for regular deposits.
+pub const RECEIPT: u32 = 100;
+/// Asset the team sponsors the pool with.
+pub const REWARD_ASSET: u32 = 1;
 
 /// Initial balances for test accounts.
 pub const ALICE_BALANCE: Balance = 100;
 pub const BOB_BALANCE: Balance = 100;
+pub const TEAM_BALANCE: Balance = 1_000;
 
 /// Test externalities builder.
 #[derive(Default)]
@@ -84,12 +299,29 @@ impl ExtBuilder {
 			.unwrap();
 
 		pallet_balances::GenesisConfig::<Runtime> {
-			balances: vec![(ALICE, ALICE_BALANCE), (BOB, BOB_BALANCE)],
+			balances: vec![
+				(ALICE, ALICE_BALANCE),
+				(BOB, BOB_BALANCE),
+				(TEAM, TEAM_BALANCE),
+				(RESERVE, TEAM_BALANCE),
+			],
 			..Default::default()
 		}
 		.assimilate_storage(&mut storage)
 		.unwrap();
 
+		pallet_assets::GenesisConfig::<Runtime> {
+			assets: vec![(REWARD_ASSET, TEAM, true, 1)],
+			accounts: vec![(REWARD_ASSET, TEAM, TEAM_BALANCE)],
+			..Default::default()
+		}
+		.assimilate_storage(&mut storage)
+		.unwrap();
+
+		native_pools::GenesisConfig::<Runtime>::default()
+			.assimilate_storage(&mut storage)
+			.unwrap();
+
 		storage.into()
 	}
 }
\ No newline at end of file
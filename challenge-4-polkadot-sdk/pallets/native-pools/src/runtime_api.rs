@@ -0,0 +1,27 @@
+//! Runtime API definition for the native pools pallet.
+
+use super::*;
+use codec::Codec;
+
+polkadot_sdk::sp_api::decl_runtime_apis! {
+	/// Queries the state of native pool deposits.
+	pub trait NativePoolsApi<AccountId, Balance, AssetId>
+	where
+		AccountId: Codec,
+		Balance: Codec,
+		AssetId: Codec,
+	{
+		/// Returns the exit fee `who` would pay to withdraw `amount` of pooled tokens now.
+		fn exit_fee(who: AccountId, amount: Balance) -> Balance;
+
+		/// Returns the amount of tokens `who` owns in the compounding vault.
+		fn compounding_balance(who: AccountId) -> Balance;
+
+		/// Returns the pending rewards of the regular deposit of `who` in each reward asset.
+		fn pending_asset_rewards(who: AccountId) -> Vec<(AssetId, Balance)>;
+
+		/// Returns the annual percentage rate of the native pool over the trailing `days` days,
+		/// such as 7 or 30.
+		fn trailing_apr(days: u32) -> FixedU128;
+	}
+}
@@ -1,7 +1,15 @@
 #![cfg(test)]
 
 use super::*;
-use polkadot_sdk::frame_support::{assert_noop, assert_ok};
+use polkadot_sdk::frame_support::{
+    assert_noop, assert_ok,
+    traits::fungible::{InspectHold, MutateFreeze, NativeOrWithId},
+};
+use polkadot_sdk::sp_runtime::testing::UintAuthorityId;
+use polkadot_sdk::sp_core::offchain::{
+    testing::{TestOffchainExt, TestTransactionPoolExt},
+    OffchainDbExt, OffchainWorkerExt, TransactionPoolExt,
+};
 use mock::*;
 
 /// Happy‑path flow: user deposits, rewards are added, user withdraws all.
@@ -67,4 +75,1026 @@ fn deposit_fails_for_zero_amount() {
             Error::<Runtime>::ZeroAmount
         );
     });
-}
\ No newline at end of file
+}
+
+/// Vesting tokens committed to the pool earn rewards and stay frozen after withdrawal.
+#[test]
+fn deposit_locked_works() {
+    ExtBuilder::build().execute_with(|| {
+        // Bob has 60 tokens frozen by vesting.
+        assert_ok!(PalletBalances::set_freeze(&VestingFreezeId::get(), &BOB, 60));
+
+        // Only vesting tokens can be committed.
+        assert_noop!(
+            NativePools::deposit_locked(RuntimeOrigin::signed(BOB), 70),
+            Error::<Runtime>::InsufficientLockedBalance
+        );
+        assert_ok!(NativePools::deposit_locked(RuntimeOrigin::signed(BOB), 60));
+        assert_eq!(NativePools::total_deposited(), 60);
+        assert_eq!(NativePools::locked_deposits(&BOB), 60);
+        assert_eq!(PalletBalances::balance_on_hold(&HoldReason::LockedDeposit.into(), &BOB), 60);
+
+        // Bob's tokens never reach the pool account.
+        assert_eq!(PalletBalances::free_balance(&NativePools::account_id()), 0);
+
+        // Rewards are earned on the committed tokens.
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 30));
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(BOB), None));
+
+        // The hold is released, the reward is spendable and the vesting freeze still applies.
+        assert_eq!(PalletBalances::balance_on_hold(&HoldReason::LockedDeposit.into(), &BOB), 0);
+        assert_eq!(NativePools::locked_deposits(&BOB), 0);
+        assert_eq!(PalletBalances::free_balance(&BOB), BOB_BALANCE + 30);
+        assert_eq!(
+            PalletBalances::reducible_balance(&BOB, Preservation::Expendable, Fortitude::Polite),
+            BOB_BALANCE + 30 - 60
+        );
+    });
+}
+
+/// With a vesting curve set, rewards are paid out through `RewardVesting`.
+#[test]
+fn rewards_vest_when_curve_is_set() {
+    ExtBuilder::build().execute_with(|| {
+        let curve = VestingCurve { period: 10, period_count: 5 };
+        assert_noop!(
+            NativePools::set_reward_vesting(RuntimeOrigin::signed(ALICE), Some(curve)),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            NativePools::set_reward_vesting(
+                RuntimeOrigin::root(),
+                Some(VestingCurve { period: 10, period_count: 0 })
+            ),
+            Error::<Runtime>::InvalidVestingCurve
+        );
+        assert_ok!(NativePools::set_reward_vesting(RuntimeOrigin::root(), Some(curve)));
+
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 40));
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 10));
+        assert_ok!(NativePools::claim_rewards(RuntimeOrigin::signed(ALICE)));
+        assert_eq!(VestedRewards::get(), vec![(ALICE, 10, 10, 5)]);
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE - 40 + 10);
+
+        // Principal is still returned liquid.
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 4));
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(ALICE), None));
+        assert_eq!(VestedRewards::get(), vec![(ALICE, 10, 10, 5), (ALICE, 4, 10, 5)]);
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE + 14);
+
+        // Rewards that cannot be vested are held rather than blocking the withdrawal.
+        VestingFails::set(true);
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 40));
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 6));
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(ALICE), None));
+        assert_eq!(VestedRewards::get().len(), 2);
+        assert_eq!(NativePools::held_rewards(&ALICE), 6);
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE + 14);
+        assert_noop!(
+            NativePools::vest_held_rewards(RuntimeOrigin::signed(ALICE)),
+            DispatchError::Other("Cannot vest")
+        );
+
+        // Once vesting works again, the held rewards vest too.
+        VestingFails::set(false);
+        assert_ok!(NativePools::vest_held_rewards(RuntimeOrigin::signed(ALICE)));
+        assert_eq!(VestedRewards::get()[2], (ALICE, 6, 10, 5));
+        assert_eq!(NativePools::held_rewards(&ALICE), 0);
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE + 20);
+        assert_noop!(
+            NativePools::vest_held_rewards(RuntimeOrigin::signed(ALICE)),
+            Error::<Runtime>::NoHeldRewards
+        );
+    });
+}
+
+/// Deposits locked for a longer term earn more and cannot be withdrawn early.
+#[test]
+fn lock_terms_weigh_rewards_and_block_withdrawal() {
+    ExtBuilder::build().execute_with(|| {
+        System::set_block_number(1);
+        assert_noop!(
+            NativePools::deposit_for_term(RuntimeOrigin::signed(BOB), 50, 3),
+            Error::<Runtime>::UnknownLockTerm
+        );
+
+        // Alice deposits unlocked, Bob locks the same amount for the doubling term.
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 50));
+        assert_ok!(NativePools::deposit_for_term(RuntimeOrigin::signed(BOB), 50, 2));
+        assert_eq!(NativePools::total_deposited(), 100);
+        assert_eq!(NativePools::total_shares(), 150);
+        assert_eq!(NativePools::deposits(&BOB).unwrap().unlock_block, 21);
+
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 30));
+        assert_eq!(NativePools::calculate_pending_rewards(&ALICE).unwrap(), 10);
+        assert_eq!(NativePools::calculate_pending_rewards(&BOB).unwrap(), 20);
+
+        assert_noop!(
+            NativePools::withdraw(RuntimeOrigin::signed(BOB), None),
+            Error::<Runtime>::DepositLocked
+        );
+
+        System::set_block_number(21);
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(BOB), None));
+        assert_eq!(PalletBalances::free_balance(&BOB), BOB_BALANCE + 20);
+        assert_eq!(NativePools::total_shares(), 50);
+    });
+}
+
+/// Lock term multipliers end at the unlock block, their rewards being paid out.
+#[test]
+fn lock_term_multipliers_end_at_unlock() {
+    ExtBuilder::build().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 50));
+        assert_ok!(NativePools::deposit_for_term(RuntimeOrigin::signed(BOB), 50, 2));
+        assert_ok!(NativePools::deposit_position(RuntimeOrigin::signed(ALICE), 50, 1));
+        assert_eq!(NativePools::total_shares(), 225);
+        assert_eq!(BoostExpiryBlocks::<Runtime>::get().to_vec(), vec![11, 21]);
+
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 45));
+        assert_eq!(NativePools::pending_position_rewards(0), 15);
+
+        // The position earns its multiplier until block 11 only.
+        System::set_block_number(11);
+        let alice_balance = PalletBalances::free_balance(&ALICE);
+        NativePools::on_initialize(11);
+        assert_eq!(PalletBalances::free_balance(&ALICE), alice_balance + 15);
+        assert_eq!(NativePools::positions(0).unwrap().shares, 50);
+        assert_eq!(NativePools::pending_position_rewards(0), 0);
+        assert_eq!(NativePools::total_shares(), 200);
+        assert_eq!(BoostExpiryBlocks::<Runtime>::get().to_vec(), vec![21]);
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 40));
+        assert_eq!(NativePools::pending_position_rewards(0), 10);
+        assert_eq!(NativePools::calculate_pending_rewards(&BOB).unwrap(), 40);
+
+        // No rewards accrue while paused, so the multiplier of Bob ends once unpaused.
+        assert_ok!(NativePools::pause(RuntimeOrigin::root()));
+        System::set_block_number(21);
+        NativePools::on_initialize(21);
+        assert_eq!(NativePools::deposits(&BOB).unwrap().shares, 100);
+        assert_ok!(NativePools::unpause(RuntimeOrigin::root()));
+        System::set_block_number(25);
+        NativePools::on_initialize(25);
+        assert_eq!(NativePools::deposits(&BOB).unwrap().shares, 50);
+        assert_eq!(PalletBalances::free_balance(&BOB), BOB_BALANCE - 50 + 40);
+        assert!(BoostExpiryBlocks::<Runtime>::get().is_empty());
+
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 30));
+        assert_eq!(NativePools::calculate_pending_rewards(&ALICE).unwrap(), 30);
+        assert_eq!(NativePools::calculate_pending_rewards(&BOB).unwrap(), 10);
+        assert_eq!(NativePools::pending_position_rewards(0), 20);
+    });
+}
+
+/// With an unbonding period, withdrawn principal is paid out later or can be rebonded.
+#[test]
+fn unbonding_withdraw_and_rebond() {
+    ExtBuilder::build().execute_with(|| {
+        UnbondingPeriod::set(5);
+        System::set_block_number(1);
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 50));
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(BOB), 50));
+
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(ALICE), Some(30)));
+        System::set_block_number(2);
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(ALICE), Some(10)));
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE - 50);
+        assert_eq!(
+            NativePools::unbonding(&ALICE).to_vec(),
+            vec![
+                UnlockChunk { value: 30, unlock_block: 6 },
+                UnlockChunk { value: 10, unlock_block: 7 },
+            ]
+        );
+        System::set_block_number(3);
+        assert_noop!(
+            NativePools::withdraw(RuntimeOrigin::signed(ALICE), Some(5)),
+            Error::<Runtime>::TooManyUnlockingChunks
+        );
+
+        // Unbonding tokens no longer earn rewards.
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 60));
+        assert_eq!(NativePools::calculate_pending_rewards(&ALICE).unwrap(), 10);
+        assert_eq!(NativePools::calculate_pending_rewards(&BOB).unwrap(), 50);
+
+        assert_noop!(
+            NativePools::withdraw_unbonded(RuntimeOrigin::signed(ALICE)),
+            Error::<Runtime>::NoUnbondedFunds
+        );
+        assert_noop!(
+            NativePools::rebond(RuntimeOrigin::signed(ALICE), 41),
+            Error::<Runtime>::InsufficientUnbonding
+        );
+
+        // Rebonding is a deposit, subject to the deposit limits.
+        assert_ok!(NativePools::set_deposit_limits(RuntimeOrigin::root(), 0, Some(20), None));
+        assert_noop!(
+            NativePools::rebond(RuntimeOrigin::signed(ALICE), 15),
+            Error::<Runtime>::AccountLimitExceeded
+        );
+        assert_ok!(NativePools::set_deposit_limits(RuntimeOrigin::root(), 0, None, None));
+
+        // Rebonding takes from the latest chunk first and pays out pending rewards.
+        assert_ok!(NativePools::rebond(RuntimeOrigin::signed(ALICE), 15));
+        assert_eq!(
+            NativePools::unbonding(&ALICE).to_vec(),
+            vec![UnlockChunk { value: 25, unlock_block: 6 }]
+        );
+        assert_eq!(NativePools::deposits(&ALICE).unwrap().amount, 25);
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE - 50 + 10);
+
+        System::set_block_number(6);
+        assert_ok!(NativePools::withdraw_unbonded(RuntimeOrigin::signed(ALICE)));
+        assert!(NativePools::unbonding(&ALICE).is_empty());
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE - 25 + 10);
+    });
+}
+
+/// The exit fee declines with deposit age and is shared between stakers and the treasury.
+#[test]
+fn exit_fee_is_redistributed() {
+    ExtBuilder::build().execute_with(|| {
+        let fee = ExitFee {
+            initial: Perbill::from_percent(40),
+            decay_period: 10,
+            treasury_share: Perbill::from_percent(50),
+        };
+        assert_noop!(
+            NativePools::set_exit_fee(
+                RuntimeOrigin::root(),
+                Some(ExitFee { decay_period: 0, ..fee })
+            ),
+            Error::<Runtime>::InvalidExitFee
+        );
+        assert_ok!(NativePools::set_exit_fee(RuntimeOrigin::root(), Some(fee)));
+
+        System::set_block_number(1);
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 50));
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(BOB), 50));
+
+        // Halfway through the decay period the fee is 20%.
+        System::set_block_number(6);
+        assert_eq!(NativePools::exit_fee(&ALICE, 50), 10);
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(ALICE), None));
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE - 10);
+        assert_eq!(PalletBalances::free_balance(&TREASURY), 5);
+        assert_eq!(NativePools::calculate_pending_rewards(&BOB).unwrap(), 5);
+
+        // After the decay period withdrawals are free.
+        System::set_block_number(11);
+        assert_eq!(NativePools::exit_fee(&BOB, 50), 0);
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(BOB), None));
+        assert_eq!(PalletBalances::free_balance(&BOB), BOB_BALANCE + 5);
+    });
+}
+
+/// Rewards of the compounding vault are folded into its principal.
+#[test]
+fn compounding_vault_works() {
+    ExtBuilder::build().execute_with(|| {
+        assert_ok!(NativePools::deposit_compounding(RuntimeOrigin::signed(ALICE), 40));
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(BOB), 40));
+
+        // Half of the rewards go to the vault and are compounded at once.
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 20));
+        assert_eq!(NativePools::compounding_balance(&ALICE), 50);
+        assert_eq!(NativePools::total_deposited(), 90);
+
+        // New vault shares are priced at the current exchange rate.
+        assert_ok!(NativePools::deposit_compounding(RuntimeOrigin::signed(BOB), 25));
+        assert_eq!(NativePools::compound_deposits(&BOB).unwrap().shares, 20);
+
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 23));
+        assert_eq!(NativePools::compounding_balance(&ALICE), 60);
+        assert_eq!(NativePools::compounding_balance(&BOB), 30);
+
+        assert_ok!(NativePools::withdraw_compounding(RuntimeOrigin::signed(ALICE), None));
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE + 20);
+        assert_eq!(NativePools::total_compound_shares(), 20);
+        assert_eq!(NativePools::compounding_balance(&BOB), 30);
+    });
+}
+
+/// Donations to a nearly empty vault do not let its first depositor take later deposits.
+#[test]
+fn vault_share_price_cannot_be_inflated() {
+    ExtBuilder::build().execute_with(|| {
+        VaultVirtualShares::set(1_000);
+        assert_ok!(NativePools::deposit_compounding(RuntimeOrigin::signed(ALICE), 1));
+        assert_eq!(NativePools::compound_deposits(&ALICE).unwrap().shares, 1);
+
+        // The vault is the only deposit, so it receives the whole donation.
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 500));
+        assert_noop!(
+            NativePools::deposit_compounding(RuntimeOrigin::signed(BOB), 1),
+            Error::<Runtime>::ZeroAmount
+        );
+        assert_ok!(NativePools::deposit_compounding(RuntimeOrigin::signed(BOB), 100));
+        assert_eq!(NativePools::compounding_balance(&BOB), 99);
+        assert_eq!(NativePools::compounding_balance(&ALICE), 1);
+
+        // Receipts cannot be used to donate to the vault either.
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 10));
+        assert_noop!(
+            NativePools::transfer_receipt(
+                RuntimeOrigin::signed(ALICE),
+                NativePools::vault_account(),
+                10
+            ),
+            Error::<Runtime>::VaultTransfer
+        );
+    });
+}
+
+/// Receipt tokens carry the deposit and its future rewards to their new holder.
+#[test]
+fn receipt_transfer_moves_deposit_and_rewards() {
+    ExtBuilder::build().execute_with(|| {
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 50));
+        assert_eq!(NativePools::receipt_balance(&ALICE), 50);
+
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 10));
+        assert_noop!(
+            NativePools::transfer_receipt(RuntimeOrigin::signed(ALICE), BOB, 51),
+            Error::<Runtime>::InsufficientReceipts
+        );
+
+        // Alice is paid the rewards earned so far.
+        assert_ok!(NativePools::transfer_receipt(RuntimeOrigin::signed(ALICE), BOB, 20));
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE - 50 + 10);
+        assert_eq!(NativePools::receipt_balance(&BOB), 20);
+        assert_eq!(NativePools::deposits(&ALICE).unwrap().amount, 30);
+        assert_eq!(NativePools::deposits(&BOB).unwrap().amount, 20);
+
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 25));
+        assert_eq!(NativePools::calculate_pending_rewards(&ALICE).unwrap(), 15);
+        assert_eq!(NativePools::calculate_pending_rewards(&BOB).unwrap(), 10);
+
+        // Withdrawing burns the receipts.
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(BOB), None));
+        assert_eq!(NativePools::receipt_balance(&BOB), 0);
+        assert_eq!(PalletBalances::free_balance(&BOB), BOB_BALANCE + 20 + 10);
+    });
+}
+
+/// Receipt tokens moved in the assets pallet leave the deposit behind, which then needs as
+/// many receipt tokens to be withdrawn.
+#[test]
+fn withdrawals_burn_exactly_the_withdrawn_receipts() {
+    ExtBuilder::build().execute_with(|| {
+        System::set_block_number(1);
+        assert!(<Assets as fungibles::Inspect<_>>::asset_exists(RECEIPT));
+
+        // Deposits with a lock term are backed by receipts as well.
+        assert_ok!(NativePools::deposit_for_term(RuntimeOrigin::signed(ALICE), 30, 1));
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 20));
+        assert_eq!(NativePools::receipt_balance(&ALICE), 50);
+        assert_noop!(
+            NativePools::transfer_receipt(RuntimeOrigin::signed(ALICE), BOB, 20),
+            Error::<Runtime>::DepositLocked
+        );
+
+        assert_ok!(<Assets as fungibles::Mutate<_>>::transfer(
+            RECEIPT,
+            &ALICE,
+            &BOB,
+            30,
+            Preservation::Expendable
+        ));
+        System::set_block_number(11);
+        assert_noop!(
+            NativePools::withdraw(RuntimeOrigin::signed(ALICE), Some(21)),
+            Error::<Runtime>::InsufficientReceipts
+        );
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(ALICE), Some(20)));
+        assert_eq!(NativePools::receipt_balance(&ALICE), 0);
+        assert_eq!(NativePools::deposits(&ALICE).unwrap().amount, 30);
+
+        // Bob does not get the deposit, but can hand the receipts back.
+        assert_eq!(NativePools::deposits(&BOB), None);
+        assert_ok!(<Assets as fungibles::Mutate<_>>::transfer(
+            RECEIPT,
+            &BOB,
+            &ALICE,
+            30,
+            Preservation::Expendable
+        ));
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(ALICE), None));
+        assert_eq!(NativePools::receipt_balance(&ALICE), 0);
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE);
+    });
+}
+
+/// Positions are NFT items whose rewards and withdrawal follow their owner.
+#[test]
+fn positions_follow_their_owner() {
+    ExtBuilder::build().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(NativePools::deposit_position(RuntimeOrigin::signed(ALICE), 50, 1));
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(BOB), 25));
+        let collection = NativePools::position_collection().unwrap();
+        assert_eq!(NativePools::positions(0).unwrap().shares, 75);
+
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 20));
+        assert_eq!(NativePools::pending_position_rewards(0), 15);
+
+        // Items can only be moved by the pool, which pays out the rewards earned so far.
+        assert_noop!(
+            Nfts::transfer(RuntimeOrigin::signed(ALICE), collection, 0, BOB),
+            polkadot_sdk::pallet_nfts::Error::<Runtime>::ItemLocked
+        );
+        assert_ok!(NativePools::transfer_position(RuntimeOrigin::signed(ALICE), 0, BOB));
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE - 50 + 15);
+
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 40));
+        assert_noop!(
+            NativePools::withdraw_position(RuntimeOrigin::signed(ALICE), 0),
+            Error::<Runtime>::NotPositionOwner
+        );
+        assert_noop!(
+            NativePools::withdraw_position(RuntimeOrigin::signed(BOB), 0),
+            Error::<Runtime>::DepositLocked
+        );
+
+        System::set_block_number(11);
+        assert_ok!(NativePools::withdraw_position(RuntimeOrigin::signed(BOB), 0));
+        assert_eq!(PalletBalances::free_balance(&BOB), BOB_BALANCE - 25 + 50 + 30);
+        assert!(NativePools::positions(0).is_none());
+        assert_eq!(NativePools::total_shares(), 25);
+    });
+}
+
+/// Rewards in other assets are shared out per share and paid with the native rewards.
+#[test]
+fn asset_rewards_work() {
+    ExtBuilder::build().execute_with(|| {
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 30));
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(BOB), 10));
+
+        assert_noop!(
+            NativePools::deposit_asset_rewards(RuntimeOrigin::signed(TEAM), REWARD_ASSET, 40),
+            Error::<Runtime>::UnknownRewardAsset
+        );
+        assert_ok!(NativePools::add_reward_asset(RuntimeOrigin::root(), REWARD_ASSET));
+        assert_noop!(
+            NativePools::add_reward_asset(RuntimeOrigin::root(), RECEIPT),
+            Error::<Runtime>::TooManyRewardAssets
+        );
+
+        assert_ok!(NativePools::deposit_asset_rewards(RuntimeOrigin::signed(TEAM), REWARD_ASSET, 40));
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 8));
+        assert_eq!(NativePools::pending_asset_rewards(&ALICE), vec![(REWARD_ASSET, 30)]);
+
+        assert_ok!(NativePools::claim_rewards(RuntimeOrigin::signed(ALICE)));
+        assert_eq!(Assets::balance(REWARD_ASSET, ALICE), 30);
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE - 30 + 6);
+
+        // Adding to the deposit settles the rewards earned so far.
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 10));
+        assert_ok!(NativePools::deposit_asset_rewards(RuntimeOrigin::signed(TEAM), REWARD_ASSET, 50));
+        assert_eq!(NativePools::pending_asset_rewards(&ALICE), vec![(REWARD_ASSET, 40)]);
+        assert_eq!(NativePools::pending_asset_rewards(&BOB), vec![(REWARD_ASSET, 20)]);
+
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(BOB), None));
+        assert_eq!(Assets::balance(REWARD_ASSET, BOB), 20);
+
+        // The compounding vault takes no part of the asset rewards.
+        assert_ok!(NativePools::deposit_compounding(RuntimeOrigin::signed(BOB), 40));
+        assert_ok!(NativePools::deposit_asset_rewards(RuntimeOrigin::signed(TEAM), REWARD_ASSET, 40));
+        assert_eq!(NativePools::pending_asset_rewards(&ALICE), vec![(REWARD_ASSET, 80)]);
+    });
+}
+
+/// Token pools accept any token, the native currency included, and pay native rewards.
+#[test]
+fn token_pools_work() {
+    ExtBuilder::build().execute_with(|| {
+        let asset = NativeOrWithId::WithId(REWARD_ASSET);
+        assert_noop!(
+            NativePools::deposit_token(RuntimeOrigin::signed(ALICE), asset.clone(), 10),
+            Error::<Runtime>::UnknownTokenPool
+        );
+        assert_ok!(NativePools::create_token_pool(RuntimeOrigin::root(), asset.clone()));
+        assert_noop!(
+            NativePools::create_token_pool(RuntimeOrigin::root(), asset.clone()),
+            Error::<Runtime>::TokenPoolExists
+        );
+        assert_ok!(NativePools::create_token_pool(RuntimeOrigin::root(), NativeOrWithId::Native));
+
+        assert_ok!(Assets::transfer(RuntimeOrigin::signed(TEAM), REWARD_ASSET, ALICE, 100));
+        assert_ok!(NativePools::deposit_token(RuntimeOrigin::signed(ALICE), asset.clone(), 60));
+        assert_ok!(NativePools::deposit_token(
+            RuntimeOrigin::signed(BOB),
+            NativeOrWithId::Native,
+            20
+        ));
+        assert_eq!(Assets::balance(REWARD_ASSET, ALICE), 40);
+
+        assert_ok!(NativePools::deposit_token_rewards(RuntimeOrigin::root(), asset.clone(), 30));
+        assert_ok!(NativePools::deposit_token_rewards(
+            RuntimeOrigin::root(),
+            NativeOrWithId::Native,
+            10
+        ));
+        assert_eq!(NativePools::pending_token_rewards(&asset, &ALICE), 30);
+        assert_eq!(NativePools::pending_token_rewards(&NativeOrWithId::Native, &BOB), 10);
+
+        assert_ok!(NativePools::withdraw_token(RuntimeOrigin::signed(ALICE), asset.clone(), None));
+        assert_eq!(Assets::balance(REWARD_ASSET, ALICE), 100);
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE + 30);
+
+        assert_ok!(NativePools::claim_token_rewards(
+            RuntimeOrigin::signed(BOB),
+            NativeOrWithId::Native
+        ));
+        assert_eq!(PalletBalances::free_balance(&BOB), BOB_BALANCE - 20 + 10);
+        assert_eq!(NativePools::token_pools(NativeOrWithId::Native).unwrap().total_deposited, 20);
+    });
+}
+
+/// Token pools apply the pause, the deposit limits, the entry deposit, the lock terms and the
+/// pool change hooks like the native pool.
+#[test]
+fn token_pools_follow_the_native_pool_rules() {
+    ExtBuilder::build().execute_with(|| {
+        System::set_block_number(1);
+        EntryDeposit::set(5);
+        let asset = NativeOrWithId::WithId(REWARD_ASSET);
+        assert_ok!(NativePools::create_token_pool(RuntimeOrigin::root(), asset.clone()));
+        assert_ok!(Assets::transfer(RuntimeOrigin::signed(TEAM), REWARD_ASSET, ALICE, 100));
+        assert_ok!(Assets::transfer(RuntimeOrigin::signed(TEAM), REWARD_ASSET, BOB, 100));
+        assert_ok!(NativePools::set_token_pool_limits(
+            RuntimeOrigin::root(),
+            asset.clone(),
+            DepositLimits { min_deposit: 10, max_per_account: Some(60), pool_cap: Some(100) }
+        ));
+
+        assert_noop!(
+            NativePools::deposit_token(RuntimeOrigin::signed(ALICE), asset.clone(), 9),
+            Error::<Runtime>::DepositTooSmall
+        );
+        assert_noop!(
+            NativePools::deposit_token(RuntimeOrigin::signed(ALICE), asset.clone(), 61),
+            Error::<Runtime>::AccountLimitExceeded
+        );
+        assert_ok!(NativePools::deposit_token_for_term(
+            RuntimeOrigin::signed(ALICE),
+            asset.clone(),
+            50,
+            2
+        ));
+        assert_noop!(
+            NativePools::deposit_token(RuntimeOrigin::signed(BOB), asset.clone(), 51),
+            Error::<Runtime>::PoolCapExceeded
+        );
+        assert_ok!(NativePools::deposit_token(RuntimeOrigin::signed(BOB), asset.clone(), 50));
+        assert_eq!(NativePools::token_pools(&asset).unwrap().total_shares, 150);
+        assert_eq!(
+            PalletBalances::balance_on_hold(&HoldReason::StorageDeposit.into(), &ALICE),
+            5
+        );
+
+        assert_ok!(NativePools::pause(RuntimeOrigin::root()));
+        assert_noop!(
+            NativePools::deposit_token(RuntimeOrigin::signed(BOB), asset.clone(), 10),
+            Error::<Runtime>::Paused
+        );
+        assert_noop!(
+            NativePools::deposit_token_rewards(RuntimeOrigin::root(), asset.clone(), 30),
+            Error::<Runtime>::Paused
+        );
+        assert_ok!(NativePools::unpause(RuntimeOrigin::root()));
+
+        // The locked deposit of Alice earns double until block 21.
+        assert_ok!(NativePools::deposit_token_rewards(RuntimeOrigin::root(), asset.clone(), 30));
+        assert_eq!(NativePools::pending_token_rewards(&asset, &ALICE), 20);
+        assert_noop!(
+            NativePools::withdraw_token(RuntimeOrigin::signed(ALICE), asset.clone(), None),
+            Error::<Runtime>::DepositLocked
+        );
+        System::set_block_number(21);
+        NativePools::on_initialize(21);
+        assert_eq!(NativePools::token_deposits(&asset, ALICE).unwrap().shares, 50);
+        assert_eq!(NativePools::token_pools(&asset).unwrap().total_shares, 100);
+
+        assert_ok!(NativePools::deposit_token_rewards(RuntimeOrigin::root(), asset.clone(), 20));
+        assert_ok!(NativePools::withdraw_token(RuntimeOrigin::signed(ALICE), asset.clone(), None));
+        assert_eq!(Assets::balance(REWARD_ASSET, ALICE), 100);
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE + 30);
+        assert_eq!(NativePools::pending_token_rewards(&asset, &BOB), 20);
+
+        assert_eq!(
+            PoolChanges::get(),
+            vec![
+                PoolChange::TokenDeposit(asset.clone(), ALICE, 50),
+                PoolChange::TokenDeposit(asset.clone(), BOB, 50),
+                PoolChange::TokenRewards(asset.clone(), 30),
+                PoolChange::TokenClaim(asset.clone(), ALICE, 20),
+                PoolChange::TokenRewards(asset.clone(), 20),
+                PoolChange::TokenClaim(asset.clone(), ALICE, 10),
+                PoolChange::TokenWithdraw(asset, ALICE, 50),
+            ]
+        );
+    });
+}
+
+/// Deposits respect the governance limits and new entries take a storage deposit.
+#[test]
+fn deposit_limits_and_entry_deposit() {
+    ExtBuilder::build().execute_with(|| {
+        EntryDeposit::set(5);
+        assert_ok!(NativePools::set_deposit_limits(RuntimeOrigin::root(), 10, Some(40), Some(60)));
+
+        assert_noop!(
+            NativePools::deposit(RuntimeOrigin::signed(ALICE), 9),
+            Error::<Runtime>::DepositTooSmall
+        );
+        assert_noop!(
+            NativePools::deposit(RuntimeOrigin::signed(ALICE), 41),
+            Error::<Runtime>::AccountLimitExceeded
+        );
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 30));
+        assert_noop!(
+            NativePools::deposit(RuntimeOrigin::signed(ALICE), 11),
+            Error::<Runtime>::AccountLimitExceeded
+        );
+        assert_noop!(
+            NativePools::deposit(RuntimeOrigin::signed(BOB), 31),
+            Error::<Runtime>::PoolCapExceeded
+        );
+
+        // Only the first deposit of an account takes the storage deposit.
+        assert_eq!(
+            PalletBalances::balance_on_hold(&HoldReason::StorageDeposit.into(), &ALICE),
+            5
+        );
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 10));
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE - 40 - 5);
+
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(ALICE), None));
+        assert_eq!(
+            PalletBalances::balance_on_hold(&HoldReason::StorageDeposit.into(), &ALICE),
+            0
+        );
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE);
+
+        // Positions and received receipt tokens count against the account limit.
+        assert_ok!(NativePools::deposit_position(RuntimeOrigin::signed(ALICE), 30, 0));
+        assert_noop!(
+            NativePools::deposit(RuntimeOrigin::signed(ALICE), 11),
+            Error::<Runtime>::AccountLimitExceeded
+        );
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(BOB), 20));
+        assert_noop!(
+            NativePools::transfer_receipt(RuntimeOrigin::signed(BOB), ALICE, 11),
+            Error::<Runtime>::AccountLimitExceeded
+        );
+
+        // Vault deposits take their own storage deposit.
+        assert_ok!(NativePools::deposit_compounding(RuntimeOrigin::signed(BOB), 10));
+        assert_eq!(
+            PalletBalances::balance_on_hold(&HoldReason::StorageDeposit.into(), &BOB),
+            10
+        );
+        assert_ok!(NativePools::withdraw_compounding(RuntimeOrigin::signed(BOB), None));
+        assert_eq!(
+            PalletBalances::balance_on_hold(&HoldReason::StorageDeposit.into(), &BOB),
+            5
+        );
+    });
+}
+
+/// A paused pool only lets depositors take their principal out.
+#[test]
+fn pause_and_emergency_withdraw() {
+    ExtBuilder::build().execute_with(|| {
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 50));
+        assert_ok!(NativePools::deposit_position(RuntimeOrigin::signed(ALICE), 20, 1));
+        assert_ok!(NativePools::deposit_compounding(RuntimeOrigin::signed(BOB), 30));
+        assert_ok!(NativePools::create_token_pool(RuntimeOrigin::root(), NativeOrWithId::Native));
+        assert_ok!(NativePools::deposit_token(
+            RuntimeOrigin::signed(BOB),
+            NativeOrWithId::Native,
+            20
+        ));
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 10));
+        assert_ok!(NativePools::deposit_token_rewards(
+            RuntimeOrigin::root(),
+            NativeOrWithId::Native,
+            10
+        ));
+        let alice_balance = PalletBalances::free_balance(&ALICE);
+
+        assert_noop!(
+            NativePools::emergency_withdraw(RuntimeOrigin::signed(ALICE)),
+            Error::<Runtime>::NotPaused
+        );
+        assert_noop!(NativePools::pause(RuntimeOrigin::signed(ALICE)), DispatchError::BadOrigin);
+        assert_ok!(NativePools::pause(RuntimeOrigin::root()));
+
+        assert_noop!(
+            NativePools::deposit(RuntimeOrigin::signed(BOB), 10),
+            Error::<Runtime>::Paused
+        );
+        assert_noop!(
+            NativePools::claim_rewards(RuntimeOrigin::signed(ALICE)),
+            Error::<Runtime>::Paused
+        );
+        assert_noop!(
+            NativePools::deposit_rewards(RuntimeOrigin::root(), 10),
+            Error::<Runtime>::Paused
+        );
+
+        // Nothing that pays out rewards goes through.
+        assert_noop!(
+            NativePools::withdraw(RuntimeOrigin::signed(ALICE), None),
+            Error::<Runtime>::Paused
+        );
+        assert_noop!(
+            NativePools::transfer_receipt(RuntimeOrigin::signed(ALICE), BOB, 10),
+            Error::<Runtime>::Paused
+        );
+        assert_noop!(
+            NativePools::withdraw_position(RuntimeOrigin::signed(ALICE), 0),
+            Error::<Runtime>::Paused
+        );
+        assert_noop!(
+            NativePools::transfer_position(RuntimeOrigin::signed(ALICE), 0, BOB),
+            Error::<Runtime>::Paused
+        );
+        assert_noop!(
+            NativePools::withdraw_compounding(RuntimeOrigin::signed(BOB), None),
+            Error::<Runtime>::Paused
+        );
+        assert_noop!(
+            NativePools::withdraw_token(RuntimeOrigin::signed(BOB), NativeOrWithId::Native, None),
+            Error::<Runtime>::Paused
+        );
+
+        // The principal is returned and the rewards are forfeited.
+        assert_ok!(NativePools::emergency_withdraw(RuntimeOrigin::signed(ALICE)));
+        assert_ok!(NativePools::emergency_withdraw_position(RuntimeOrigin::signed(ALICE), 0));
+        assert_eq!(PalletBalances::free_balance(&ALICE), alice_balance + 70);
+        assert_eq!(NativePools::receipt_balance(&ALICE), 0);
+        assert!(NativePools::positions(0).is_none());
+
+        assert_ok!(NativePools::emergency_withdraw_compounding(RuntimeOrigin::signed(BOB)));
+        assert_ok!(NativePools::emergency_withdraw_token(
+            RuntimeOrigin::signed(BOB),
+            NativeOrWithId::Native
+        ));
+        // Vault rewards compounded before the pause are principal.
+        assert_eq!(PalletBalances::free_balance(&BOB), BOB_BALANCE + 2);
+        assert_eq!(NativePools::total_deposited(), 0);
+        assert_eq!(NativePools::total_shares(), 0);
+        assert_eq!(NativePools::token_pools(NativeOrWithId::Native).unwrap().total_deposited, 0);
+
+        assert_ok!(NativePools::unpause(RuntimeOrigin::root()));
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(BOB), 10));
+    });
+}
+
+/// Rewards are paid to the reward destination of each depositor.
+#[test]
+fn reward_destinations_work() {
+    ExtBuilder::build().execute_with(|| {
+        assert_ok!(NativePools::set_reward_destination(
+            RuntimeOrigin::signed(ALICE),
+            RewardDestination::Account(TREASURY)
+        ));
+        assert_ok!(NativePools::set_reward_destination(
+            RuntimeOrigin::signed(BOB),
+            RewardDestination::Compound
+        ));
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 50));
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(BOB), 50));
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 20));
+
+        assert_ok!(NativePools::claim_rewards(RuntimeOrigin::signed(ALICE)));
+        assert_eq!(PalletBalances::free_balance(&TREASURY), 10);
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE - 50);
+
+        // Compounded rewards are added to the deposit, backed by receipt tokens.
+        assert_ok!(NativePools::claim_rewards(RuntimeOrigin::signed(BOB)));
+        assert_eq!(NativePools::deposits(&BOB).unwrap().amount, 60);
+        assert_eq!(NativePools::receipt_balance(&BOB), 60);
+        assert_eq!(NativePools::total_deposited(), 110);
+
+        // Without a deposit left, compounded rewards are paid to the depositor.
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 22));
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(BOB), None));
+        assert_eq!(PalletBalances::free_balance(&BOB), BOB_BALANCE + 22);
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(ALICE), None));
+        assert_eq!(PalletBalances::free_balance(&TREASURY), 20);
+        assert_eq!(PalletBalances::free_balance(&ALICE), ALICE_BALANCE);
+
+        assert_ok!(NativePools::set_reward_destination(
+            RuntimeOrigin::signed(ALICE),
+            RewardDestination::Stay
+        ));
+        assert!(!RewardDestinations::<Runtime>::contains_key(&ALICE));
+    });
+}
+
+/// Reward deposits are recorded in a ring buffer from which the trailing APR is computed.
+#[test]
+fn reward_snapshots_and_apr() {
+    ExtBuilder::build().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 50));
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 5));
+        assert_eq!(
+            NativePools::reward_snapshots(0),
+            Some(RewardSnapshot {
+                block: 1,
+                rewards: 5,
+                total_deposited: 50,
+                acc_reward_per_share: 100_000_000_000,
+            })
+        );
+
+        System::set_block_number(11);
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 5));
+
+        // A day is 10 blocks, so only the second deposit is in the last day.
+        assert_eq!(
+            NativePools::trailing_apr(1),
+            FixedU128::saturating_from_rational(365u32, 10u32)
+        );
+        assert_eq!(
+            NativePools::trailing_apr(7),
+            FixedU128::saturating_from_rational(2 * 365u32, 7 * 10u32)
+        );
+        assert_eq!(NativePools::trailing_apr(0), FixedU128::zero());
+
+        // Rewards of the same day, exit fees included, add to its snapshot.
+        assert_ok!(NativePools::set_exit_fee(
+            RuntimeOrigin::root(),
+            Some(ExitFee {
+                initial: Perbill::from_percent(10),
+                decay_period: 100,
+                treasury_share: Perbill::zero(),
+            })
+        ));
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(BOB), 50));
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(BOB), Some(20)));
+        assert_eq!(NativePools::next_reward_snapshot(), 2);
+        assert_eq!(NativePools::reward_snapshots(1).unwrap().rewards, 7);
+
+        // The oldest snapshot is overwritten once the buffer is full.
+        System::set_block_number(21);
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 5));
+        System::set_block_number(31);
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 5));
+        assert_eq!(NativePools::next_reward_snapshot(), 1);
+        assert_eq!(NativePools::reward_snapshots(0).unwrap().block, 31);
+        assert_eq!(RewardSnapshots::<Runtime>::iter().count(), 3);
+    });
+}
+
+/// Scheduled rewards are distributed once a day, from the reserve.
+#[test]
+fn scheduled_rewards_are_distributed_once_a_day() {
+    ExtBuilder::build().execute_with(|| {
+        System::set_block_number(25);
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 50));
+        assert_noop!(
+            NativePools::distribute_scheduled_reward(RuntimeOrigin::root(), 2),
+            Error::<Runtime>::NoScheduledReward
+        );
+        assert_noop!(
+            NativePools::set_scheduled_reward(RuntimeOrigin::signed(ALICE), Some(10)),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(NativePools::set_scheduled_reward(RuntimeOrigin::root(), Some(10)));
+
+        assert_noop!(
+            NativePools::distribute_scheduled_reward(RuntimeOrigin::signed(ALICE), 2),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            NativePools::distribute_scheduled_reward(RuntimeOrigin::root(), 1),
+            Error::<Runtime>::InvalidRewardDay
+        );
+        assert_ok!(NativePools::distribute_scheduled_reward(RuntimeOrigin::root(), 2));
+        assert_eq!(PalletBalances::free_balance(&RESERVE), TEAM_BALANCE - 10);
+        assert_eq!(NativePools::calculate_pending_rewards(&ALICE).unwrap(), 10);
+        assert_noop!(
+            NativePools::distribute_scheduled_reward(RuntimeOrigin::root(), 2),
+            Error::<Runtime>::RewardAlreadyDistributed
+        );
+
+        System::set_block_number(30);
+        assert_ok!(NativePools::distribute_scheduled_reward(RuntimeOrigin::root(), 3));
+        assert_eq!(NativePools::last_reward_day(), Some(3));
+
+        // Members of the reward team can distribute and deposit rewards too.
+        assert_noop!(
+            NativePools::set_reward_team_member(RuntimeOrigin::signed(BOB), BOB, true),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(NativePools::set_reward_team_member(RuntimeOrigin::root(), BOB, true));
+        System::set_block_number(40);
+        assert_ok!(NativePools::distribute_scheduled_reward(RuntimeOrigin::signed(BOB), 4));
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::signed(BOB), 5));
+        assert_eq!(PalletBalances::free_balance(&BOB), BOB_BALANCE - 5);
+
+        assert_ok!(NativePools::set_reward_team_member(RuntimeOrigin::root(), BOB, false));
+        assert_noop!(
+            NativePools::deposit_rewards(RuntimeOrigin::signed(BOB), 5),
+            DispatchError::BadOrigin
+        );
+    });
+}
+
+/// The keeper submits the distribution of the day once, signed with its key.
+#[test]
+fn keeper_submits_scheduled_reward_once() {
+    let (offchain, _) = TestOffchainExt::new();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+    let mut ext = ExtBuilder::build();
+    ext.register_extension(OffchainDbExt::new(offchain.clone()));
+    ext.register_extension(OffchainWorkerExt::new(offchain));
+    ext.register_extension(TransactionPoolExt::new(pool));
+
+    ext.execute_with(|| {
+        UintAuthorityId::set_all_keys(vec![TEAM]);
+
+        // Nothing is submitted without a scheduled reward.
+        NativePools::offchain_worker(25);
+        assert!(pool_state.read().transactions.is_empty());
+
+        assert_ok!(NativePools::set_scheduled_reward(RuntimeOrigin::root(), Some(10)));
+        NativePools::offchain_worker(25);
+        let tx = pool_state.write().transactions.pop().unwrap();
+        let tx = Extrinsic::decode(&mut &*tx).unwrap();
+        assert_eq!(
+            tx.function,
+            RuntimeCall::NativePools(Call::distribute_scheduled_reward { day: 2 })
+        );
+
+        // The day was already submitted by this node.
+        NativePools::offchain_worker(26);
+        assert!(pool_state.read().transactions.is_empty());
+
+        NativePools::offchain_worker(30);
+        assert_eq!(pool_state.read().transactions.len(), 1);
+    });
+}
+
+/// Pool changes are reported to `OnPoolChange` and stakes can be read through `PoolInspect`.
+#[test]
+fn pool_changes_are_reported() {
+    ExtBuilder::build().execute_with(|| {
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(ALICE), 50));
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 20));
+        assert_ok!(NativePools::claim_rewards(RuntimeOrigin::signed(ALICE)));
+        assert_ok!(NativePools::deposit(RuntimeOrigin::signed(BOB), 30));
+        assert_eq!(<NativePools as PoolInspect<_>>::stake_of(&ALICE), 50);
+        assert_eq!(<NativePools as PoolInspect<_>>::total_stake(), 80);
+
+        assert_ok!(NativePools::deposit_rewards(RuntimeOrigin::root(), 8));
+        assert_ok!(NativePools::withdraw(RuntimeOrigin::signed(ALICE), Some(10)));
+        assert_eq!(<NativePools as PoolInspect<_>>::stake_of(&ALICE), 40);
+
+        assert_eq!(
+            PoolChanges::get(),
+            vec![
+                PoolChange::Deposit(ALICE, 50),
+                PoolChange::Rewards(20),
+                PoolChange::Claim(ALICE, 20),
+                PoolChange::Deposit(BOB, 30),
+                PoolChange::Rewards(8),
+                PoolChange::Claim(ALICE, 5),
+                PoolChange::Withdraw(ALICE, 10),
+            ]
+        );
+
+        // Every other way of changing a stake is reported too.
+        PoolChanges::set(vec![]);
+        assert_ok!(NativePools::deposit_for_term(RuntimeOrigin::signed(BOB), 10, 0));
+        assert_ok!(NativePools::deposit_position(RuntimeOrigin::signed(BOB), 20, 0));
+        assert_ok!(NativePools::transfer_position(RuntimeOrigin::signed(BOB), 0, ALICE));
+        assert_ok!(NativePools::transfer_receipt(RuntimeOrigin::signed(ALICE), BOB, 5));
+        assert_ok!(NativePools::deposit_compounding(RuntimeOrigin::signed(BOB), 10));
+        assert_ok!(NativePools::withdraw_compounding(RuntimeOrigin::signed(BOB), None));
+        assert_eq!(<NativePools as PoolInspect<_>>::stake_of(&ALICE), 55);
+        assert_eq!(<NativePools as PoolInspect<_>>::stake_of(&BOB), 45);
+
+        assert_eq!(
+            PoolChanges::get(),
+            vec![
+                PoolChange::Claim(BOB, 3),
+                PoolChange::Deposit(BOB, 10),
+                PoolChange::Deposit(BOB, 20),
+                PoolChange::Withdraw(BOB, 20),
+                PoolChange::Deposit(ALICE, 20),
+                PoolChange::Withdraw(ALICE, 5),
+                PoolChange::Deposit(BOB, 5),
+                PoolChange::Deposit(BOB, 10),
+                PoolChange::Withdraw(BOB, 10),
+            ]
+        );
+    });
+}
@@ -0,0 +1,65 @@
+//! Ethereum addresses and `personal_sign` signatures used by Ethereum claims.
+
+use super::*;
+use polkadot_sdk::sp_io::{crypto::secp256k1_ecdsa_recover, hashing::keccak_256};
+
+/// A 20-byte Ethereum address.
+#[derive(
+	Clone,
+	Copy,
+	Default,
+	Encode,
+	Decode,
+	DecodeWithMemTracking,
+	MaxEncodedLen,
+	TypeInfo,
+	Debug,
+	PartialEq,
+	Eq,
+)]
+pub struct EthereumAddress(pub [u8; 20]);
+
+/// A 65-byte recoverable ECDSA signature, as produced by `personal_sign`.
+#[derive(Clone, Encode, Decode, DecodeWithMemTracking, TypeInfo, Debug, PartialEq, Eq)]
+pub struct EcdsaSignature(pub [u8; 65]);
+
+/// Returns the lowercase hex encoding of `data` without a `0x` prefix.
+fn to_ascii_hex(data: &[u8]) -> Vec<u8> {
+	let mut r = Vec::with_capacity(data.len() * 2);
+	let mut push_nibble = |n| r.push(if n < 10 { b'0' + n } else { b'a' - 10 + n });
+	for &b in data.iter() {
+		push_nibble(b / 16);
+		push_nibble(b % 16);
+	}
+	r
+}
+
+/// Builds the message that `personal_sign` signs for `prefix` followed by the hex encoding of
+/// `what`.
+pub fn ethereum_signable_message(prefix: &[u8], what: &[u8]) -> Vec<u8> {
+	let what = to_ascii_hex(what);
+	let mut l = prefix.len() + what.len();
+	let mut rev = Vec::new();
+	while l > 0 {
+		rev.push(b'0' + (l % 10) as u8);
+		l /= 10;
+	}
+	let mut v = b"\x19Ethereum Signed Message:\n".to_vec();
+	v.extend(rev.into_iter().rev());
+	v.extend_from_slice(prefix);
+	v.extend_from_slice(&what);
+	v
+}
+
+/// Recovers the Ethereum address that signed `prefix` followed by the hex encoding of `what`.
+pub fn eth_recover(
+	signature: &EcdsaSignature,
+	prefix: &[u8],
+	what: &[u8],
+) -> Option<EthereumAddress> {
+	let msg = keccak_256(&ethereum_signable_message(prefix, what));
+	let public = secp256k1_ecdsa_recover(&signature.0, &msg).ok()?;
+	let mut address = EthereumAddress::default();
+	address.0.copy_from_slice(&keccak_256(&public[..])[12..]);
+	Some(address)
+}
@@ -8,29 +8,49 @@
 use frame::prelude::*;
 use polkadot_sdk::polkadot_sdk_frame as frame;
 use polkadot_sdk::frame_support::{
-	ensure,
+	defensive, ensure,
 	traits::{
-		Currency, LockIdentifier, LockableCurrency, WithdrawReasons, ExistenceRequirement, Get,
+		fungible::{Inspect, Mutate, MutateFreeze},
+		fungibles,
+		tokens::{DepositConsequence, Fortitude, Preservation, Provenance},
+		Get, StorageVersion,
 	},
+	PalletId,
 };
 use polkadot_sdk::sp_runtime::{
 	traits::{
-		AccountIdConversion, AtLeast32Bit, CheckedAdd, CheckedMul, Convert, Saturating, Zero,
+		AccountIdConversion, AtLeast32Bit, CheckedAdd, CheckedMul, Convert, Hash as HashT, One,
+		Saturating, UniqueSaturatedInto, Zero,
 	},
 	ArithmeticError,
 };
-use polkadot_sdk::sp_std::{cmp::PartialEq, prelude::*, vec::Vec};
+use polkadot_sdk::sp_std::{
+	cmp::{PartialEq, Reverse},
+	prelude::*,
+	vec::Vec,
+};
 
-pub const VESTING_ID: LockIdentifier = *b"vesting ";
+// Re-export all pallet parts, this is needed to properly import the pallet into the runtime.
+pub use ethereum::{EcdsaSignature, EthereumAddress};
+pub use pallet::*;
 
 type BalanceOf<T> =
-	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	<<T as Config>::Currency as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
 type BlockNumberFor<T> = frame_system::pallet_prelude::BlockNumberFor<T>;
 type VestingScheduleOf<T> = VestingSchedule<BlockNumberFor<T>, BalanceOf<T>>;
 
+pub mod ethereum;
+pub mod migrations;
 mod mock;
+pub mod runtime_api;
 mod tests;
 
+/// The in-code storage version.
+const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
+/// A free-form label of a vesting schedule, such as the name of the grant.
+pub type ScheduleLabel = BoundedVec<u8, ConstU32<32>>;
+
 /// A vesting schedule over a currency.
 #[derive(Clone, Encode, Decode, MaxEncodedLen, TypeInfo, Debug, PartialEq, DecodeWithMemTracking)]
 pub struct VestingSchedule<BlockNumber, Balance> {
@@ -38,6 +58,21 @@ pub struct VestingSchedule<BlockNumber, Balance> {
 	pub period: BlockNumber,
 	pub period_count: u32,
 	pub per_period: Balance,
+	/// An optional label of the grant.
+	pub label: Option<ScheduleLabel>,
+	/// An optional category of the grant, such as team, advisor, seed or community.
+	pub category: Option<u32>,
+}
+
+/// Aggregated amounts of the native vesting schedules in a category.
+#[derive(
+	Clone, Default, Encode, Decode, MaxEncodedLen, TypeInfo, Debug, PartialEq, DecodeWithMemTracking,
+)]
+pub struct CategoryTotals<Balance> {
+	/// The amount still locked.
+	pub locked: Balance,
+	/// The amount vested so far.
+	pub vested: Balance,
 }
 
 impl<
@@ -76,6 +111,24 @@ impl<
 		self.per_period.saturating_mul(elapsed_as_balance)
 	}
 
+	/// Returns the first block after `now` at which more of the schedule vests, or `None` if
+	/// it is fully vested by `now`.
+	pub fn next_unlock_block(&self, now: BlockNumber) -> Option<BlockNumber> {
+		if self.period.is_zero() {
+			return None;
+		}
+		let elapsed_periods: BlockNumber = if now < self.start {
+			Zero::zero()
+		} else {
+			now.saturating_sub(self.start) / self.period
+		};
+		let next_period = elapsed_periods.saturating_add(One::one());
+		if next_period > self.period_count.into() {
+			return None;
+		}
+		Some(self.start.saturating_add(next_period.saturating_mul(self.period)))
+	}
+
 	/// Returns the remaining locked amount at the given block.
 	pub fn locked_amount<BlockNumberToBalance: Convert<BlockNumber, Balance>>(
 		&self,
@@ -92,28 +145,245 @@ impl<
 	}
 }
 
+/// A pause placed on an account's vesting, for example while it is under legal hold.
+#[derive(Clone, Encode, Decode, MaxEncodedLen, TypeInfo, Debug, PartialEq, DecodeWithMemTracking)]
+pub struct VestingFreeze<BlockNumber> {
+	/// The block at which vesting stopped.
+	pub frozen_since: BlockNumber,
+	/// The block at which vesting resumes on its own, or `None` until it is thawed.
+	pub frozen_until: Option<BlockNumber>,
+}
+
+/// The vesting state of an account, as returned by the runtime API.
+#[derive(Clone, Encode, Decode, TypeInfo, Debug, PartialEq)]
+pub struct VestingDetails<BlockNumber, Balance> {
+	/// The account's vesting schedules.
+	pub schedules: Vec<VestingSchedule<BlockNumber, Balance>>,
+	/// The amount vested so far.
+	pub vested: Balance,
+	/// The amount still locked.
+	pub locked: Balance,
+	/// The freeze on the account's vesting, if any.
+	pub freeze: Option<VestingFreeze<BlockNumber>>,
+}
+
 #[frame::pallet]
 pub mod pallet {
 	use super::*;
 
 	#[pallet::config]
 	pub trait Config: polkadot_sdk::frame_system::Config {
-		type Currency: LockableCurrency<Self::AccountId, Moment = BlockNumberFor<Self>>;
+		/// The overarching freeze reason.
+		type RuntimeFreezeReason: From<FreezeReason>;
+		/// The currency whose balance is frozen while it vests.
+		type Currency: Inspect<Self::AccountId>
+			+ Mutate<Self::AccountId>
+			+ MutateFreeze<Self::AccountId, Id = Self::RuntimeFreezeReason>;
 		type BlockNumberToBalance: Convert<BlockNumberFor<Self>, BalanceOf<Self>>;
 		#[pallet::constant]
 		type MaxVestingSchedules: Get<u32>;
 		#[pallet::constant]
 		type MinVestedTransfer: Get<BalanceOf<Self>>;
+		/// The origin allowed to overwrite and remove vesting schedules.
+		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		/// Whether moving a schedule to another account needs approval from its grantor, or
+		/// from `AdminOrigin` for schedules without one.
+		#[pallet::constant]
+		type RequireTransferApproval: Get<bool>;
+		/// Identifier of the non-native assets that can be vested.
+		type AssetId: Member + Parameter + MaxEncodedLen;
+		/// The non-native assets that can be vested, escrowed in the pallet account.
+		type Assets: fungibles::Inspect<
+				Self::AccountId,
+				AssetId = Self::AssetId,
+				Balance = BalanceOf<Self>,
+			> + fungibles::Mutate<Self::AccountId>;
+		/// The pallet's account ID for escrowing vested assets.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// The text that Ethereum claim signatures must sign before the hex encoded destination.
+		#[pallet::constant]
+		type EthereumClaimPrefix: Get<&'static [u8]>;
+		/// The most nodes in the Merkle proof of an airdrop claim.
+		#[pallet::constant]
+		type MaxProofLength: Get<u32>;
+		/// The most accounts whose vesting freeze is refreshed automatically in one block.
+		///
+		/// Zero turns automatic unlocking off, so vested funds stay frozen until `claim`.
+		#[pallet::constant]
+		type MaxUnlocksPerBlock: Get<u32>;
 	}
 
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
+	/// A reason for the pallet placing a freeze on funds.
+	#[pallet::composite_enum]
+	pub enum FreezeReason {
+		/// Funds are frozen until they vest.
+		#[codec(index = 0)]
+		Vesting,
+	}
+
 	#[pallet::storage]
 	#[pallet::getter(fn vesting_schedules)]
-	pub type VestingSchedules<
-		T: Config,
-	> = StorageMap<_, Blake2_128Concat, T::AccountId, BoundedVec<VestingScheduleOf<T>, T::MaxVestingSchedules>, ValueQuery>;
+	pub type VestingSchedules<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		BoundedVec<VestingScheduleOf<T>, T::MaxVestingSchedules>,
+		ValueQuery,
+	>;
+
+	/// Accounts whose vesting is frozen.
+	#[pallet::storage]
+	#[pallet::getter(fn vesting_freezes)]
+	pub type VestingFreezes<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, VestingFreeze<BlockNumberFor<T>>, OptionQuery>;
+
+	/// Schedule transfers approved by the grantor, as `(index, new_owner)` keyed by the
+	/// current owner.
+	#[pallet::storage]
+	#[pallet::getter(fn transfer_approvals)]
+	pub type TransferApprovals<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, (u32, T::AccountId), OptionQuery>;
+
+	/// The account that funded each native vesting schedule, by index in `VestingSchedules`.
+	///
+	/// Schedules not created by a vested transfer, such as airdrop claims, have no grantor.
+	/// Trailing schedules without a grantor may be missing from the list.
+	#[pallet::storage]
+	pub type ScheduleGrantors<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		BoundedVec<Option<T::AccountId>, T::MaxVestingSchedules>,
+		ValueQuery,
+	>;
+
+	/// Vesting schedules over non-native assets.
+	#[pallet::storage]
+	#[pallet::getter(fn asset_vesting_schedules)]
+	pub type AssetVestingSchedules<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::AssetId,
+		BoundedVec<VestingScheduleOf<T>, T::MaxVestingSchedules>,
+		ValueQuery,
+	>;
+
+	/// Amount of each asset held in escrow for an account and not yet released.
+	#[pallet::storage]
+	#[pallet::getter(fn asset_escrowed)]
+	pub type AssetEscrowed<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::AssetId,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	/// Minimum amount of a vested transfer of each asset.
+	#[pallet::storage]
+	#[pallet::getter(fn asset_min_vested_transfer)]
+	pub type AssetMinVestedTransfer<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AssetId, BalanceOf<T>, ValueQuery>;
+
+	/// Merkle roots of the open airdrop campaigns.
+	///
+	/// Each leaf is the hash of `(account, amount, schedule)`, and the parent of two nodes is
+	/// the hash of the pair with the smaller node first.
+	#[pallet::storage]
+	#[pallet::getter(fn airdrops)]
+	pub type Airdrops<T: Config> = StorageMap<_, Twox64Concat, u32, T::Hash, OptionQuery>;
+
+	/// The identifier of the next airdrop campaign.
+	#[pallet::storage]
+	#[pallet::getter(fn next_airdrop_id)]
+	pub type NextAirdropId<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// Accounts that have claimed from an airdrop campaign.
+	#[pallet::storage]
+	#[pallet::getter(fn airdrop_claimed)]
+	pub type AirdropClaimed<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, u32, Blake2_128Concat, T::AccountId, (), OptionQuery>;
+
+	/// Amounts claimable by Ethereum addresses, with the part of each that vests.
+	#[pallet::storage]
+	#[pallet::getter(fn ethereum_claims)]
+	pub type EthereumClaims<T: Config> = StorageMap<
+		_,
+		Identity,
+		EthereumAddress,
+		(BalanceOf<T>, Option<VestingScheduleOf<T>>),
+		OptionQuery,
+	>;
+
+	/// Locked and vested totals of the native vesting schedules in each category.
+	///
+	/// The totals follow an account's schedules each time its vesting freeze is refreshed, for
+	/// example on `claim`.
+	#[pallet::storage]
+	#[pallet::getter(fn category_totals)]
+	pub type CategoryTotalsOf<T: Config> =
+		StorageMap<_, Twox64Concat, u32, CategoryTotals<BalanceOf<T>>, ValueQuery>;
+
+	/// The `(category, totals)` contributed by each account to `CategoryTotalsOf`, as of its
+	/// last refresh.
+	#[pallet::storage]
+	pub type AccountCategoryTotals<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		BoundedVec<(u32, CategoryTotals<BalanceOf<T>>), T::MaxVestingSchedules>,
+		ValueQuery,
+	>;
+
+	/// Accounts whose vesting freeze is refreshed in `on_initialize` of each block.
+	#[pallet::storage]
+	pub type UnlockQueue<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		BlockNumberFor<T>,
+		BoundedVec<T::AccountId, T::MaxUnlocksPerBlock>,
+		ValueQuery,
+	>;
+
+	/// The block in `UnlockQueue` at which each account is next refreshed.
+	#[pallet::storage]
+	#[pallet::getter(fn next_unlock)]
+	pub type NextUnlock<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, BlockNumberFor<T>, OptionQuery>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+			let due = UnlockQueue::<T>::take(now);
+			// Every entry is probed, including those left behind when an account is rescheduled.
+			let mut weight = T::DbWeight::get().reads_writes((due.len() as u64).saturating_add(1), 1);
+			for who in due.iter() {
+				if NextUnlock::<T>::get(who) != Some(now) {
+					continue;
+				}
+				NextUnlock::<T>::remove(who);
+				// A refresh reads and writes the schedules, freezes and category totals, on top
+				// of queueing the next one.
+				weight.saturating_accrue(T::DbWeight::get().reads_writes(5, 6));
+				match Self::refresh_lock(who) {
+					Ok((_, queued)) => weight.saturating_accrue(queued),
+					Err(_) => {
+						defensive!("vesting freeze refresh failed");
+					},
+				}
+			}
+			weight
+		}
+	}
 
 	#[pallet::error]
 	pub enum Error<T> {
@@ -126,6 +396,32 @@ pub mod pallet {
 		InvalidVestingIndex,
 		ArithmeticOverflow,
 		InvalidVestingStart,
+		/// The account's vesting is already frozen.
+		AlreadyFrozen,
+		/// The account's vesting is not frozen.
+		NotFrozen,
+		/// The account's vesting is frozen.
+		VestingFrozen,
+		/// The freeze would end before the current block.
+		InvalidFreezeEnd,
+		/// The schedule transfer has not been approved by the grantor.
+		TransferNotApproved,
+		/// The caller is not the grantor of the schedule.
+		NotGrantor,
+		/// The airdrop campaign does not exist or has been closed.
+		UnknownAirdrop,
+		/// The account has already claimed from the airdrop campaign.
+		AlreadyClaimed,
+		/// The Merkle proof does not match the campaign root.
+		InvalidProof,
+		/// The vesting schedule locks more than the claimed amount.
+		ScheduleExceedsAmount,
+		/// The signature does not recover to an Ethereum address with a claim.
+		InvalidEthereumSignature,
+		/// The account paying out the claim does not hold enough funds.
+		InsufficientPotBalance,
+		/// The still locked balance of the schedule is on hold, for example committed to a pool.
+		LockedBalanceOnHold,
 	}
 
 	#[pallet::call]
@@ -145,10 +441,7 @@ pub mod pallet {
 		#[pallet::weight(10_000)]
 		pub fn claim(origin: OriginFor<T>) -> DispatchResult {
 			let who = ensure_signed(origin)?;
-			let locked = Self::update_lock(&who)?;
-			if locked.is_zero() {
-				T::Currency::remove_lock(VESTING_ID, &who);
-			}
+			Self::update_lock(&who)?;
 			Ok(())
 		}
 
@@ -159,12 +452,20 @@ pub mod pallet {
 			who: T::AccountId,
 			schedules: Vec<VestingScheduleOf<T>>,
 		) -> DispatchResult {
-			ensure_root(origin)?;
+			T::AdminOrigin::ensure_origin(origin)?;
+			let now = frame_system::Pallet::<T>::block_number();
+			Self::ensure_not_frozen(&who, now)?;
+			for schedule in schedules.iter() {
+				Self::validate_schedule(schedule, now, T::MinVestedTransfer::get())?;
+			}
 			let bounded: BoundedVec<_, _> = schedules.try_into().map_err(|_| Error::<T>::TooManyVestingSchedules)?;
-			let total_locked = Self::calculate_total_locked_amount(&bounded)?;
-			let free = T::Currency::free_balance(&who);
+			let total_locked =
+				Self::calculate_total_locked_amount(&bounded, Self::vesting_now(&who, now))?;
+			let free = T::Currency::balance(&who);
 			ensure!(free >= total_locked, Error::<T>::InsufficientBalanceToLock);
 			VestingSchedules::<T>::insert(&who, &bounded);
+			ScheduleGrantors::<T>::remove(&who);
+			TransferApprovals::<T>::remove(&who);
 			Self::update_lock(&who)?;
 			Ok(())
 		}
@@ -177,16 +478,18 @@ pub mod pallet {
 			index: u32,
 			schedule: VestingScheduleOf<T>,
 		) -> DispatchResult {
-			ensure_root(origin)?;
-			VestingSchedules::<T>::try_mutate(&who, |vec| {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let now = frame_system::Pallet::<T>::block_number();
+			Self::ensure_not_frozen(&who, now)?;
+			VestingSchedules::<T>::try_mutate(&who, |vec| -> DispatchResult {
 				let idx = index as usize;
 				let len = vec.len();
 				ensure!(idx < len, Error::<T>::InvalidVestingIndex);
-				ensure!(!schedule.period.is_zero(), Error::<T>::ZeroVestingPeriod);
-				ensure!(schedule.period_count > 0, Error::<T>::ZeroVestingPeriodCount);
+				Self::validate_schedule(&schedule, now, T::MinVestedTransfer::get())?;
 				vec[idx] = schedule;
 				Ok(())
 			})?;
+			TransferApprovals::<T>::remove(&who);
 			Self::update_lock(&who)?;
 			Ok(())
 		}
@@ -198,74 +501,932 @@ pub mod pallet {
 			who: T::AccountId,
 			schedule_index: u32,
 		) -> DispatchResult {
-			ensure_root(origin)?;
+			T::AdminOrigin::ensure_origin(origin)?;
 			VestingSchedules::<T>::try_mutate(&who, |vec| {
 				let idx = schedule_index as usize;
 				ensure!(idx < vec.len(), Error::<T>::InvalidVestingIndex);
 				vec.remove(idx);
-				Ok(())
+				Ok::<_, DispatchError>(())
 			})?;
-			if VestingSchedules::<T>::get(&who).is_empty() {
-				T::Currency::remove_lock(VESTING_ID, &who);
-			} else {
-				Self::update_lock(&who)?;
+			Self::remove_grantor(&who, schedule_index);
+			TransferApprovals::<T>::remove(&who);
+			Self::update_lock(&who)?;
+			Ok(())
+		}
+
+		/// Stop vesting for `who` until `until`, or until it is thawed if `until` is `None`.
+		///
+		/// Nothing vests while the freeze lasts, and the start of every schedule of `who` is
+		/// moved back by the frozen duration once the freeze ends.
+		///
+		/// The dispatch origin for this call must be `AdminOrigin`.
+		#[pallet::call_index(5)]
+		#[pallet::weight(10_000)]
+		pub fn freeze_vesting(
+			origin: OriginFor<T>,
+			who: T::AccountId,
+			until: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Self::update_lock(&who)?;
+			ensure!(!VestingFreezes::<T>::contains_key(&who), Error::<T>::AlreadyFrozen);
+
+			let now = frame_system::Pallet::<T>::block_number();
+			if let Some(until) = until {
+				ensure!(until > now, Error::<T>::InvalidFreezeEnd);
+			}
+			VestingFreezes::<T>::insert(&who, VestingFreeze { frozen_since: now, frozen_until: until });
+			Ok(())
+		}
+
+		/// Resume vesting for `who`.
+		///
+		/// The dispatch origin for this call must be `AdminOrigin`.
+		#[pallet::call_index(6)]
+		#[pallet::weight(10_000)]
+		pub fn thaw_vesting(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let freeze = VestingFreezes::<T>::get(&who).ok_or(Error::<T>::NotFrozen)?;
+			let now = frame_system::Pallet::<T>::block_number();
+			let end = freeze.frozen_until.map_or(now, |until| until.min(now));
+			Self::end_freeze(&who, &freeze, end);
+			Self::update_lock(&who)?;
+			Ok(())
+		}
+
+		/// Move the schedule at `index` of the caller, together with its still locked balance,
+		/// to `new_owner`.
+		///
+		/// When `RequireTransferApproval` is set, the transfer must first be approved with
+		/// `approve_schedule_transfer`. The locked balance cannot be on hold, such as vesting
+		/// tokens committed to a pool.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		#[pallet::call_index(7)]
+		#[pallet::weight(10_000)]
+		pub fn transfer_schedule(
+			origin: OriginFor<T>,
+			index: u32,
+			new_owner: T::AccountId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			if T::RequireTransferApproval::get() {
+				ensure!(
+					TransferApprovals::<T>::get(&who) == Some((index, new_owner.clone())),
+					Error::<T>::TransferNotApproved
+				);
+			}
+			Self::do_transfer_schedule(&who, index, &new_owner)
+		}
+
+		/// Approve the move of the schedule at `index` of `owner` to `new_owner`.
+		///
+		/// The approval is dropped whenever the schedules of `owner` change.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the grantor of the schedule, or
+		/// `AdminOrigin` if the schedule has no grantor.
+		#[pallet::call_index(8)]
+		#[pallet::weight(10_000)]
+		pub fn approve_schedule_transfer(
+			origin: OriginFor<T>,
+			owner: T::AccountId,
+			index: u32,
+			new_owner: T::AccountId,
+		) -> DispatchResult {
+			ensure!(
+				(index as usize) < VestingSchedules::<T>::decode_len(&owner).unwrap_or_default(),
+				Error::<T>::InvalidVestingIndex
+			);
+			match Self::schedule_grantor(&owner, index) {
+				Some(grantor) => ensure!(ensure_signed(origin)? == grantor, Error::<T>::NotGrantor),
+				None => T::AdminOrigin::ensure_origin(origin).map(|_| ())?,
+			}
+			TransferApprovals::<T>::insert(&owner, (index, new_owner));
+			Ok(())
+		}
+
+		/// Create a vesting schedule over `asset` for `dest`, escrowing its total amount in the
+		/// pallet account.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		#[pallet::call_index(9)]
+		#[pallet::weight(10_000)]
+		pub fn vested_transfer_asset(
+			origin: OriginFor<T>,
+			asset: T::AssetId,
+			dest: T::AccountId,
+			schedule: VestingScheduleOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_vested_transfer_asset(&who, asset, &dest, schedule)
+		}
+
+		/// Release the vested amount of `asset` from escrow to the caller.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		#[pallet::call_index(10)]
+		#[pallet::weight(10_000)]
+		pub fn claim_asset(origin: OriginFor<T>, asset: T::AssetId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::release_asset(&who, asset)?;
+			Ok(())
+		}
+
+		/// Set the minimum amount of a vested transfer of `asset`.
+		///
+		/// The dispatch origin for this call must be `AdminOrigin`.
+		#[pallet::call_index(11)]
+		#[pallet::weight(10_000)]
+		pub fn set_asset_min_vested_transfer(
+			origin: OriginFor<T>,
+			asset: T::AssetId,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			AssetMinVestedTransfer::<T>::insert(asset, amount);
+			Ok(())
+		}
+
+		/// Open an airdrop campaign paid from its pot account, see [`Pallet::airdrop_account`].
+		///
+		/// The dispatch origin for this call must be `AdminOrigin`.
+		#[pallet::call_index(12)]
+		#[pallet::weight(10_000)]
+		pub fn create_airdrop(origin: OriginFor<T>, merkle_root: T::Hash) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let airdrop_id = NextAirdropId::<T>::get();
+			NextAirdropId::<T>::put(airdrop_id.checked_add(1).ok_or(Error::<T>::ArithmeticOverflow)?);
+			Airdrops::<T>::insert(airdrop_id, merkle_root);
+			Ok(())
+		}
+
+		/// Claim `amount` from an airdrop campaign for `who`, of which `schedule` stays locked
+		/// until it vests.
+		///
+		/// The dispatch origin for this call must be _None_, the Merkle proof authorizes it.
+		#[pallet::call_index(13)]
+		#[pallet::weight(
+			Weight::from_parts(10_000, 0)
+				.saturating_add(Weight::from_parts(1_000, 0).saturating_mul(proof.len() as u64))
+		)]
+		pub fn claim_airdrop(
+			origin: OriginFor<T>,
+			airdrop_id: u32,
+			who: T::AccountId,
+			amount: BalanceOf<T>,
+			schedule: VestingScheduleOf<T>,
+			proof: BoundedVec<T::Hash, T::MaxProofLength>,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			Self::check_airdrop_claim(airdrop_id, &who, amount, &schedule, &proof)?;
+
+			T::Currency::transfer(
+				&Self::airdrop_account(airdrop_id),
+				&who,
+				amount,
+				Preservation::Expendable,
+			)?;
+			VestingSchedules::<T>::try_mutate(&who, |vec| {
+				vec.try_push(schedule).map_err(|_| Error::<T>::TooManyVestingSchedules)
+			})?;
+			AirdropClaimed::<T>::insert(airdrop_id, &who, ());
+			Self::update_lock(&who)?;
+			Ok(())
+		}
+
+		/// Close an airdrop campaign and send what is left in its pot to `dest`.
+		///
+		/// The dispatch origin for this call must be `AdminOrigin`.
+		#[pallet::call_index(14)]
+		#[pallet::weight(10_000)]
+		pub fn close_airdrop(
+			origin: OriginFor<T>,
+			airdrop_id: u32,
+			dest: T::AccountId,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(Airdrops::<T>::contains_key(airdrop_id), Error::<T>::UnknownAirdrop);
+			Airdrops::<T>::remove(airdrop_id);
+
+			let pot = Self::airdrop_account(airdrop_id);
+			let remaining =
+				T::Currency::reducible_balance(&pot, Preservation::Expendable, Fortitude::Polite);
+			if !remaining.is_zero() {
+				T::Currency::transfer(&pot, &dest, remaining, Preservation::Expendable)?;
+			}
+			Ok(())
+		}
+
+		/// Register `amount` as claimable by `address`, of which `schedule` vests.
+		///
+		/// Claims are paid from [`Pallet::ethereum_claims_account`]. Registering an address
+		/// again replaces its claim.
+		///
+		/// The dispatch origin for this call must be `AdminOrigin`.
+		#[pallet::call_index(15)]
+		#[pallet::weight(10_000)]
+		pub fn register_ethereum_claim(
+			origin: OriginFor<T>,
+			address: EthereumAddress,
+			amount: BalanceOf<T>,
+			schedule: Option<VestingScheduleOf<T>>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(!amount.is_zero(), Error::<T>::AmountLow);
+			if let Some(schedule) = &schedule {
+				ensure!(!schedule.period.is_zero(), Error::<T>::ZeroVestingPeriod);
+				ensure!(schedule.period_count > 0, Error::<T>::ZeroVestingPeriodCount);
+				let locked = schedule.total_amount().ok_or(Error::<T>::ArithmeticOverflow)?;
+				ensure!(locked <= amount, Error::<T>::ScheduleExceedsAmount);
+			}
+			EthereumClaims::<T>::insert(address, (amount, schedule));
+			Ok(())
+		}
+
+		/// Claim the amount registered for the Ethereum address that signed `dest`.
+		///
+		/// `ethereum_signature` is the `personal_sign` signature of `EthereumClaimPrefix`
+		/// followed by the hex encoded SCALE encoding of `dest`.
+		///
+		/// The dispatch origin for this call must be _None_, the signature authorizes it.
+		#[pallet::call_index(16)]
+		#[pallet::weight(10_000)]
+		pub fn claim_ethereum(
+			origin: OriginFor<T>,
+			dest: T::AccountId,
+			ethereum_signature: EcdsaSignature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			let signer = Self::check_ethereum_claim(&dest, &ethereum_signature)?;
+			let (amount, schedule) =
+				EthereumClaims::<T>::take(signer).ok_or(Error::<T>::InvalidEthereumSignature)?;
+
+			T::Currency::transfer(
+				&Self::ethereum_claims_account(),
+				&dest,
+				amount,
+				Preservation::Expendable,
+			)?;
+			if let Some(schedule) = schedule {
+				VestingSchedules::<T>::try_mutate(&dest, |vec| {
+					vec.try_push(schedule).map_err(|_| Error::<T>::TooManyVestingSchedules)
+				})?;
+				Self::update_lock(&dest)?;
 			}
 			Ok(())
 		}
 	}
 
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			match call {
+				Call::claim_airdrop { airdrop_id, who, amount, schedule, proof } => {
+					Self::check_airdrop_claim(*airdrop_id, who, *amount, schedule, proof).map_err(
+						|e| match e {
+							Error::<T>::UnknownAirdrop | Error::<T>::AlreadyClaimed =>
+								InvalidTransaction::Stale,
+							Error::<T>::InvalidProof => InvalidTransaction::BadProof,
+							_ => InvalidTransaction::Call,
+						},
+					)?;
+					ValidTransaction::with_tag_prefix("VestingAirdrop")
+						.and_provides((airdrop_id, who))
+						.propagate(true)
+						.build()
+				},
+				Call::claim_ethereum { dest, ethereum_signature } => {
+					let signer =
+						Self::check_ethereum_claim(dest, ethereum_signature).map_err(|e| match e {
+							Error::<T>::InvalidEthereumSignature => InvalidTransaction::BadProof,
+							_ => InvalidTransaction::Call,
+						})?;
+					ValidTransaction::with_tag_prefix("VestingEthereumClaim")
+						.and_provides(signer)
+						.propagate(true)
+						.build()
+				},
+				_ => InvalidTransaction::Call.into(),
+			}
+		}
+	}
+
 	impl<T: Config> Pallet<T> {
+		/// Transfers `amount` from `source` to `dest` and locks it in a schedule of
+		/// `period_count` periods of `period` blocks starting now.
+		///
+		/// While `dest` still has a schedule labelled `label` with something locked, the amount
+		/// is instead added to its remaining periods, so that repeated payouts share a single
+		/// schedule. What that schedule already locks keeps vesting as before and it still ends
+		/// at the same block. Without such a schedule, an amount below `MinVestedTransfer` is
+		/// transferred without vesting.
+		///
+		/// What does not divide evenly into the periods vests with the last one, through a
+		/// second schedule under the same label.
+		pub fn vested_transfer_merging(
+			source: &T::AccountId,
+			dest: &T::AccountId,
+			amount: BalanceOf<T>,
+			period: BlockNumberFor<T>,
+			period_count: u32,
+			label: ScheduleLabel,
+		) -> DispatchResult {
+			ensure!(!period.is_zero(), Error::<T>::ZeroVestingPeriod);
+			ensure!(period_count > 0, Error::<T>::ZeroVestingPeriodCount);
+			ensure!(!VestingFreezes::<T>::contains_key(dest), Error::<T>::VestingFrozen);
+
+			T::Currency::transfer(source, dest, amount, Preservation::Expendable)?;
+
+			let now = frame_system::Pallet::<T>::block_number();
+			let label = Some(label);
+			let (changed, removed, pushed) =
+				VestingSchedules::<T>::try_mutate(dest, |vec| -> Result<_, DispatchError> {
+					// Schedules under the label that have fully vested are dropped.
+					let removed: Vec<usize> = vec
+						.iter()
+						.enumerate()
+						.filter(|(_, s)| {
+							s.label == label &&
+								s.locked_amount::<T::BlockNumberToBalance>(now).is_zero()
+						})
+						.map(|(idx, _)| idx)
+						.collect();
+					for &idx in removed.iter().rev() {
+						vec.remove(idx);
+					}
+					let mut changed = !removed.is_empty();
+					// The first schedule with the most periods left takes the amount.
+					let merged = vec
+						.iter()
+						.enumerate()
+						.filter(|(_, s)| s.label == label)
+						.map(|(idx, s)| (idx, Self::periods_left(s, now)))
+						.filter(|(_, left)| *left > 0)
+						.max_by_key(|&(idx, left)| (left, Reverse(idx)));
+					if merged.is_none() && amount < T::MinVestedTransfer::get() {
+						return Ok((changed, removed, 0..0));
+					}
+					let first = vec.len();
+					let (start, period, period_count, per_period) = match merged {
+						Some((idx, left)) => {
+							// Restart the schedule at its current period, where the amount
+							// starts vesting alongside what is still locked.
+							changed = true;
+							let schedule = &mut vec[idx];
+							let elapsed: BlockNumberFor<T> =
+								schedule.period_count.saturating_sub(left).into();
+							schedule.start = schedule
+								.start
+								.saturating_add(schedule.period.saturating_mul(elapsed));
+							schedule.period_count = left;
+							let per_period = amount / left.into();
+							schedule.per_period = schedule.per_period.saturating_add(per_period);
+							(schedule.start, schedule.period, left, per_period)
+						},
+						None => {
+							let per_period = amount / period_count.into();
+							if !per_period.is_zero() {
+								let schedule = VestingSchedule {
+									start: now,
+									period,
+									period_count,
+									per_period,
+									label: label.clone(),
+									category: None,
+								};
+								vec.try_push(schedule)
+									.map_err(|_| Error::<T>::TooManyVestingSchedules)?;
+							}
+							(now, period, period_count, per_period)
+						},
+					};
+					let remainder =
+						amount.saturating_sub(per_period.saturating_mul(period_count.into()));
+					if !remainder.is_zero() {
+						let last_period: BlockNumberFor<T> = period_count.saturating_sub(1).into();
+						let last_start = start.saturating_add(period.saturating_mul(last_period));
+						let last = vec.iter_mut().find(|s| {
+							s.label == label &&
+								s.start == last_start && s.period == period &&
+								s.period_count == 1
+						});
+						match last {
+							Some(last) => {
+								changed = true;
+								last.per_period = last.per_period.saturating_add(remainder);
+							},
+							None => {
+								let schedule = VestingSchedule {
+									start: last_start,
+									period,
+									period_count: 1,
+									per_period: remainder,
+									label,
+									category: None,
+								};
+								vec.try_push(schedule)
+									.map_err(|_| Error::<T>::TooManyVestingSchedules)?;
+							},
+						}
+					}
+					Ok((changed, removed, first..vec.len()))
+				})?;
+			// Schedules were changed or moved, so an approval could point at another one.
+			if changed {
+				TransferApprovals::<T>::remove(dest);
+			}
+			for &index in removed.iter().rev() {
+				Self::remove_grantor(dest, index as u32);
+			}
+			for index in pushed {
+				Self::push_grantor(dest, index, Some(source.clone()));
+			}
+			Self::update_lock(dest)?;
+			Ok(())
+		}
+
+		/// The number of periods of `schedule` that have not vested by block `now`.
+		fn periods_left(schedule: &VestingScheduleOf<T>, now: BlockNumberFor<T>) -> u32 {
+			if schedule.period.is_zero() || now < schedule.start {
+				return schedule.period_count;
+			}
+			let elapsed: u32 =
+				(now.saturating_sub(schedule.start) / schedule.period).unique_saturated_into();
+			schedule.period_count.saturating_sub(elapsed)
+		}
+
 		fn do_vested_transfer(
 			from: &T::AccountId,
 			to: &T::AccountId,
 			schedule: VestingScheduleOf<T>,
 		) -> DispatchResult {
 			let now = frame_system::Pallet::<T>::block_number();
+			let total = Self::validate_schedule(&schedule, now, T::MinVestedTransfer::get())?;
+			ensure!(!VestingFreezes::<T>::contains_key(to), Error::<T>::VestingFrozen);
 
-			ensure!(!schedule.period.is_zero(), Error::<T>::ZeroVestingPeriod);
-			ensure!(schedule.period_count > 0, Error::<T>::ZeroVestingPeriodCount);
-			ensure!(schedule.is_valid_start_block(now), Error::<T>::InvalidVestingStart);
-
-			let total = schedule
-				.total_amount()
-				.ok_or(Error::<T>::ArithmeticOverflow)?;
-			ensure!(total >= T::MinVestedTransfer::get(), Error::<T>::AmountLow);
-
-			let free_from = T::Currency::free_balance(from);
+			let free_from =
+				T::Currency::reducible_balance(from, Preservation::Expendable, Fortitude::Polite);
 			ensure!(free_from >= total, Error::<T>::InsufficientBalanceToLock);
 
-			T::Currency::transfer(from, to, total, ExistenceRequirement::AllowDeath)?;
+			T::Currency::transfer(from, to, total, Preservation::Expendable)?;
 
-			VestingSchedules::<T>::try_mutate(to, |vec| {
+			let index = VestingSchedules::<T>::try_mutate(to, |vec| {
 				if (vec.len() as u32) >= T::MaxVestingSchedules::get() {
 					return Err(Error::<T>::TooManyVestingSchedules.into());
 				}
 				vec.try_push(schedule).map_err(|_| Error::<T>::TooManyVestingSchedules)?;
-				Ok::<_, DispatchError>(())
+				Ok::<_, DispatchError>(vec.len() - 1)
 			})?;
+			Self::push_grantor(to, index, Some(from.clone()));
 
 			Self::update_lock(to)?;
 			Ok(())
 		}
 
-		fn update_lock(who: &T::AccountId) -> Result<BalanceOf<T>, DispatchError> {
+		/// The account ID that escrows vested assets.
+		pub fn escrow_account() -> T::AccountId {
+			T::PalletId::get().into_account_truncating()
+		}
+
+		/// Gives the escrow account a provider reference if it does not exist yet, so that it
+		/// can hold assets without a native balance.
+		///
+		/// The reference is never removed. The escrow account belongs to the pallet and must
+		/// stay alive for good, since any later vested transfer may escrow assets in it, and a
+		/// single permanent reference costs no more than the account entry itself.
+		fn ensure_escrow_exists(escrow: &T::AccountId) {
+			if !frame_system::Pallet::<T>::account_exists(escrow) {
+				frame_system::Pallet::<T>::inc_providers(escrow);
+			}
+		}
+
+		fn do_vested_transfer_asset(
+			from: &T::AccountId,
+			asset: T::AssetId,
+			to: &T::AccountId,
+			schedule: VestingScheduleOf<T>,
+		) -> DispatchResult {
+			let now = frame_system::Pallet::<T>::block_number();
+			let min = AssetMinVestedTransfer::<T>::get(&asset);
+			let total = Self::validate_schedule(&schedule, now, min)?;
+			ensure!(!VestingFreezes::<T>::contains_key(to), Error::<T>::VestingFrozen);
+
+			let escrow = Self::escrow_account();
+			Self::ensure_escrow_exists(&escrow);
+			<T::Assets as fungibles::Mutate<_>>::transfer(
+				asset.clone(),
+				from,
+				&escrow,
+				total,
+				Preservation::Expendable,
+			)?;
+
+			AssetVestingSchedules::<T>::try_mutate(to, &asset, |vec| {
+				vec.try_push(schedule).map_err(|_| Error::<T>::TooManyVestingSchedules)
+			})?;
+			AssetEscrowed::<T>::mutate(to, &asset, |held| *held = held.saturating_add(total));
+			Ok(())
+		}
+
+		/// Pays the vested part of the escrowed `asset` out to `who` and returns the amount
+		/// that stays locked.
+		fn release_asset(
+			who: &T::AccountId,
+			asset: T::AssetId,
+		) -> Result<BalanceOf<T>, DispatchError> {
+			let now = frame_system::Pallet::<T>::block_number();
+			Self::settle_freeze(who, now);
+			let schedules = AssetVestingSchedules::<T>::get(who, &asset);
+			let locked =
+				Self::calculate_total_locked_amount(&schedules, Self::vesting_now(who, now))?;
+			let releasable = AssetEscrowed::<T>::get(who, &asset).saturating_sub(locked);
+			ensure!(!releasable.is_zero(), Error::<T>::NotVested);
+
+			<T::Assets as fungibles::Mutate<_>>::transfer(
+				asset.clone(),
+				&Self::escrow_account(),
+				who,
+				releasable,
+				Preservation::Expendable,
+			)?;
+			if locked.is_zero() {
+				AssetVestingSchedules::<T>::remove(who, &asset);
+				AssetEscrowed::<T>::remove(who, &asset);
+			} else {
+				AssetEscrowed::<T>::insert(who, &asset, locked);
+			}
+			Ok(locked)
+		}
+
+		/// The pot account that pays out the claims of an airdrop campaign.
+		pub fn airdrop_account(airdrop_id: u32) -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating((b"airdrop", airdrop_id))
+		}
+
+		/// The pot account that pays out Ethereum claims.
+		pub fn ethereum_claims_account() -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating(b"ethclaim")
+		}
+
+		/// Returns the Ethereum address with a claim that signed `dest`.
+		///
+		/// Covers everything `claim_ethereum` can fail on, so that unsigned claims that would fail
+		/// never enter the transaction pool.
+		fn check_ethereum_claim(
+			dest: &T::AccountId,
+			signature: &EcdsaSignature,
+		) -> Result<EthereumAddress, Error<T>> {
+			let signer = dest
+				.using_encoded(|data| {
+					ethereum::eth_recover(signature, T::EthereumClaimPrefix::get(), data)
+				})
+				.ok_or(Error::<T>::InvalidEthereumSignature)?;
+			let (amount, schedule) =
+				EthereumClaims::<T>::get(signer).ok_or(Error::<T>::InvalidEthereumSignature)?;
+			if schedule.is_some() {
+				ensure!(!VestingFreezes::<T>::contains_key(dest), Error::<T>::VestingFrozen);
+				ensure!(
+					VestingSchedules::<T>::decode_len(dest).unwrap_or_default() <
+						T::MaxVestingSchedules::get() as usize,
+					Error::<T>::TooManyVestingSchedules
+				);
+			}
+			Self::ensure_can_pay(&Self::ethereum_claims_account(), dest, amount)?;
+			Ok(signer)
+		}
+
+		/// Checks that `who` can claim `amount` and `schedule` from the airdrop campaign.
+		///
+		/// Covers everything `claim_airdrop` can fail on, so that unsigned claims that would fail
+		/// never enter the transaction pool.
+		fn check_airdrop_claim(
+			airdrop_id: u32,
+			who: &T::AccountId,
+			amount: BalanceOf<T>,
+			schedule: &VestingScheduleOf<T>,
+			proof: &[T::Hash],
+		) -> Result<(), Error<T>> {
+			let root = Airdrops::<T>::get(airdrop_id).ok_or(Error::<T>::UnknownAirdrop)?;
+			ensure!(!AirdropClaimed::<T>::contains_key(airdrop_id, who), Error::<T>::AlreadyClaimed);
+
+			let leaf = T::Hashing::hash_of(&(who, amount, schedule));
+			let computed = proof.iter().fold(leaf, |node, sibling| {
+				if node <= *sibling {
+					T::Hashing::hash_of(&(node, sibling))
+				} else {
+					T::Hashing::hash_of(&(sibling, node))
+				}
+			});
+			ensure!(computed == root, Error::<T>::InvalidProof);
+
+			ensure!(!schedule.period.is_zero(), Error::<T>::ZeroVestingPeriod);
+			ensure!(schedule.period_count > 0, Error::<T>::ZeroVestingPeriodCount);
+			let locked = schedule.total_amount().ok_or(Error::<T>::ArithmeticOverflow)?;
+			ensure!(locked <= amount, Error::<T>::ScheduleExceedsAmount);
+			ensure!(
+				VestingSchedules::<T>::decode_len(who).unwrap_or_default() <
+					T::MaxVestingSchedules::get() as usize,
+				Error::<T>::TooManyVestingSchedules
+			);
+			ensure!(!VestingFreezes::<T>::contains_key(who), Error::<T>::VestingFrozen);
+			Self::ensure_can_pay(&Self::airdrop_account(airdrop_id), who, amount)
+		}
+
+		/// Checks that `pot` can transfer `amount` to `who`.
+		fn ensure_can_pay(
+			pot: &T::AccountId,
+			who: &T::AccountId,
+			amount: BalanceOf<T>,
+		) -> Result<(), Error<T>> {
+			let available =
+				T::Currency::reducible_balance(pot, Preservation::Expendable, Fortitude::Polite);
+			ensure!(available >= amount, Error::<T>::InsufficientPotBalance);
+			ensure!(
+				T::Currency::can_deposit(who, amount, Provenance::Extant) ==
+					DepositConsequence::Success,
+				Error::<T>::AmountLow
+			);
+			Ok(())
+		}
+
+		fn do_transfer_schedule(
+			from: &T::AccountId,
+			index: u32,
+			to: &T::AccountId,
+		) -> DispatchResult {
+			ensure!(!VestingFreezes::<T>::contains_key(from), Error::<T>::VestingFrozen);
+			ensure!(!VestingFreezes::<T>::contains_key(to), Error::<T>::VestingFrozen);
+
+			let schedule = VestingSchedules::<T>::try_mutate(from, |vec| {
+				let idx = index as usize;
+				ensure!(idx < vec.len(), Error::<T>::InvalidVestingIndex);
+				Ok::<_, DispatchError>(vec.remove(idx))
+			})?;
+			let grantor = Self::remove_grantor(from, index);
+			TransferApprovals::<T>::remove(from);
+
+			// Shrink the freeze on the old owner first so the locked balance can be moved.
+			let now = frame_system::Pallet::<T>::block_number();
+			let locked = schedule.locked_amount::<T::BlockNumberToBalance>(now);
+			Self::update_lock(from)?;
+			ensure!(
+				T::Currency::reducible_balance(from, Preservation::Expendable, Fortitude::Polite) >=
+					locked,
+				Error::<T>::LockedBalanceOnHold
+			);
+			if !locked.is_zero() {
+				T::Currency::transfer(from, to, locked, Preservation::Expendable)?;
+			}
+
+			let index = VestingSchedules::<T>::try_mutate(to, |vec| {
+				vec.try_push(schedule).map_err(|_| Error::<T>::TooManyVestingSchedules)?;
+				Ok::<_, DispatchError>(vec.len() - 1)
+			})?;
+			Self::push_grantor(to, index, grantor);
+			TransferApprovals::<T>::remove(to);
+			Self::update_lock(to)?;
+			Ok(())
+		}
+
+		/// The account that funded the schedule at `index` of `who`, if any.
+		pub fn schedule_grantor(who: &T::AccountId, index: u32) -> Option<T::AccountId> {
+			ScheduleGrantors::<T>::get(who).get(index as usize).cloned().flatten()
+		}
+
+		/// Records `grantor` for the schedule just pushed at `index` of the schedules of `who`.
+		fn push_grantor(who: &T::AccountId, index: usize, grantor: Option<T::AccountId>) {
+			// Trailing schedules without a grantor need no entry.
+			if grantor.is_none() {
+				return;
+			}
+			ScheduleGrantors::<T>::mutate(who, |grantors| {
+				// Schedules pushed without a grantor are missing from the list, fill them in.
+				while grantors.len() < index {
+					let _ = grantors.try_push(None);
+				}
+				// Cannot fail, there is at most one grantor per schedule.
+				let _ = grantors.try_push(grantor);
+			});
+		}
+
+		/// Forgets the grantor of the schedule at `index` of `who`, which was just removed, and
+		/// returns it.
+		fn remove_grantor(who: &T::AccountId, index: u32) -> Option<T::AccountId> {
+			ScheduleGrantors::<T>::mutate_exists(who, |maybe_grantors| {
+				let grantors = maybe_grantors.as_mut()?;
+				let grantor = ((index as usize) < grantors.len())
+					.then(|| grantors.remove(index as usize))
+					.flatten();
+				if grantors.is_empty() {
+					*maybe_grantors = None;
+				}
+				grantor
+			})
+		}
+
+		/// Checks that `schedule` can be created at block `now` with a total amount of at least
+		/// `min` and returns that total.
+		fn validate_schedule(
+			schedule: &VestingScheduleOf<T>,
+			now: BlockNumberFor<T>,
+			min: BalanceOf<T>,
+		) -> Result<BalanceOf<T>, DispatchError> {
+			ensure!(!schedule.period.is_zero(), Error::<T>::ZeroVestingPeriod);
+			ensure!(schedule.period_count > 0, Error::<T>::ZeroVestingPeriodCount);
+			ensure!(schedule.is_valid_start_block(now), Error::<T>::InvalidVestingStart);
+
+			let total = schedule
+				.total_amount()
+				.ok_or(Error::<T>::ArithmeticOverflow)?;
+			ensure!(total >= min, Error::<T>::AmountLow);
+			Ok(total)
+		}
+
+		/// Sets the vesting freeze on `who` to the amount still locked by its schedules.
+		pub(crate) fn update_lock(who: &T::AccountId) -> Result<BalanceOf<T>, DispatchError> {
+			Self::refresh_lock(who).map(|(locked, _)| locked)
+		}
+
+		/// Does what `update_lock` does, also returning the weight of queueing the next refresh.
+		fn refresh_lock(who: &T::AccountId) -> Result<(BalanceOf<T>, Weight), DispatchError> {
+			let now = frame_system::Pallet::<T>::block_number();
+			Self::settle_freeze(who, now);
 			let schedules = VestingSchedules::<T>::get(who);
-			let total_locked = Self::calculate_total_locked_amount(&schedules)?;
+			let vesting_now = Self::vesting_now(who, now);
+			let total_locked = Self::calculate_total_locked_amount(&schedules, vesting_now)?;
+			Self::update_category_totals(who, &schedules, vesting_now);
+			let reason: T::RuntimeFreezeReason = FreezeReason::Vesting.into();
 			if total_locked.is_zero() {
-				T::Currency::remove_lock(VESTING_ID, who);
+				T::Currency::thaw(&reason, who)?;
 			} else {
-				T::Currency::set_lock(VESTING_ID, who, total_locked, WithdrawReasons::all());
+				T::Currency::set_freeze(&reason, who, total_locked)?;
+			}
+
+			// A frozen account is next refreshed when its freeze ends, if ever.
+			let next_unlock = match VestingFreezes::<T>::get(who) {
+				Some(freeze) => freeze.frozen_until,
+				None => schedules.iter().filter_map(|s| s.next_unlock_block(now)).min(),
+			};
+			let queued = Self::schedule_unlock(who, next_unlock);
+			Ok((total_locked, queued))
+		}
+
+		/// Queues `who` to have its vesting freeze refreshed at block `at`, or at one of the
+		/// few blocks after it if that block is full, and returns the weight of doing so.
+		///
+		/// When those are all full, blocks further and further away are tried before giving up,
+		/// leaving `who` unqueued. Queueing is best effort, since a late refresh only delays the
+		/// unlock until the next `claim`.
+		fn schedule_unlock(who: &T::AccountId, at: Option<BlockNumberFor<T>>) -> Weight {
+			const SEARCH_BLOCKS: u32 = 10;
+			const FALLBACK_PROBES: u32 = 16;
+
+			if T::MaxUnlocksPerBlock::get() == 0 {
+				return Weight::zero();
+			}
+			let db = T::DbWeight::get();
+			let mut weight = db.reads(1);
+			let current = NextUnlock::<T>::get(who);
+			let scheduled = match (current, at) {
+				(Some(current), Some(at)) =>
+					current >= at && current < at.saturating_add(SEARCH_BLOCKS.into()),
+				(current, at) => current == at,
+			};
+			if scheduled {
+				return weight;
+			}
+			if let Some(current) = current {
+				weight.saturating_accrue(db.reads_writes(1, 2));
+				NextUnlock::<T>::remove(who);
+				UnlockQueue::<T>::mutate_exists(current, |maybe_queue| {
+					if let Some(queue) = maybe_queue {
+						queue.retain(|queued| queued != who);
+						if queue.is_empty() {
+							*maybe_queue = None;
+						}
+					}
+				});
+			}
+			let Some(at) = at else { return weight };
+			let nearby = (0..SEARCH_BLOCKS).map(|offset| at.saturating_add(offset.into()));
+			let fallback = (0..FALLBACK_PROBES).map(|shift| {
+				at.saturating_add(SEARCH_BLOCKS.saturating_mul(2u32.saturating_pow(shift)).into())
+			});
+			for block in nearby.chain(fallback) {
+				weight.saturating_accrue(db.reads_writes(1, 1));
+				let queued = UnlockQueue::<T>::mutate(block, |queue| {
+					if queue.is_full() {
+						// Drop entries of accounts that have since moved to another block.
+						weight.saturating_accrue(db.reads(queue.len() as u64));
+						queue.retain(|queued| NextUnlock::<T>::get(queued) == Some(block));
+					}
+					queue.try_push(who.clone())
+				});
+				if queued.is_ok() {
+					weight.saturating_accrue(db.writes(1));
+					NextUnlock::<T>::insert(who, block);
+					return weight;
+				}
+			}
+			log::warn!(
+				target: "runtime::vesting",
+				"unlock queue full, vesting freeze refreshed on next claim only",
+			);
+			weight
+		}
+
+		/// Replaces the contribution of `who` to `CategoryTotalsOf` with that of `schedules`
+		/// evaluated at block `now`.
+		fn update_category_totals(
+			who: &T::AccountId,
+			schedules: &BoundedVec<VestingScheduleOf<T>, T::MaxVestingSchedules>,
+			now: BlockNumberFor<T>,
+		) {
+			for (category, old) in AccountCategoryTotals::<T>::take(who) {
+				CategoryTotalsOf::<T>::mutate(category, |totals| {
+					totals.locked = totals.locked.saturating_sub(old.locked);
+					totals.vested = totals.vested.saturating_sub(old.vested);
+				});
+			}
+
+			let mut contributions = BoundedVec::<_, T::MaxVestingSchedules>::new();
+			for schedule in schedules.iter() {
+				let Some(category) = schedule.category else { continue };
+				let locked = schedule.locked_amount::<T::BlockNumberToBalance>(now);
+				let vested = schedule.vested_amount::<T::BlockNumberToBalance>(now);
+				CategoryTotalsOf::<T>::mutate(category, |totals| {
+					totals.locked = totals.locked.saturating_add(locked);
+					totals.vested = totals.vested.saturating_add(vested);
+				});
+				// Cannot fail, there is at most one contribution per schedule.
+				let _ = contributions.try_push((category, CategoryTotals { locked, vested }));
+			}
+			if !contributions.is_empty() {
+				AccountCategoryTotals::<T>::insert(who, contributions);
+			}
+		}
+
+		/// Checks that the vesting of `who` is not frozen at block `now`, ending a freeze whose
+		/// end block has been reached.
+		///
+		/// The shift applied when a freeze ends would otherwise also move schedules set while
+		/// it lasted.
+		fn ensure_not_frozen(who: &T::AccountId, now: BlockNumberFor<T>) -> DispatchResult {
+			Self::settle_freeze(who, now);
+			ensure!(!VestingFreezes::<T>::contains_key(who), Error::<T>::VestingFrozen);
+			Ok(())
+		}
+
+		/// Ends the freeze of `who` if its end block has been reached.
+		fn settle_freeze(who: &T::AccountId, now: BlockNumberFor<T>) {
+			if let Some(freeze) = VestingFreezes::<T>::get(who) {
+				if let Some(until) = freeze.frozen_until.filter(|until| *until <= now) {
+					Self::end_freeze(who, &freeze, until);
+				}
+			}
+		}
+
+		/// Removes the freeze of `who` that ended at `end` and moves the start of its schedules
+		/// back by the frozen duration.
+		fn end_freeze(
+			who: &T::AccountId,
+			freeze: &VestingFreeze<BlockNumberFor<T>>,
+			end: BlockNumberFor<T>,
+		) {
+			let frozen_for = end.saturating_sub(freeze.frozen_since);
+			let shift = |vec: &mut BoundedVec<VestingScheduleOf<T>, T::MaxVestingSchedules>| {
+				for schedule in vec.iter_mut() {
+					schedule.start = schedule.start.saturating_add(frozen_for);
+				}
+			};
+			VestingSchedules::<T>::mutate(who, shift);
+			for asset in AssetVestingSchedules::<T>::iter_key_prefix(who).collect::<Vec<_>>() {
+				AssetVestingSchedules::<T>::mutate(who, asset, shift);
+			}
+			VestingFreezes::<T>::remove(who);
+		}
+
+		/// The block at which the schedules of `who` are evaluated, which lags `now` by the
+		/// time the account's vesting has been frozen.
+		fn vesting_now(who: &T::AccountId, now: BlockNumberFor<T>) -> BlockNumberFor<T> {
+			match VestingFreezes::<T>::get(who) {
+				Some(freeze) => {
+					let end = freeze.frozen_until.map_or(now, |until| until.min(now));
+					now.saturating_sub(end.saturating_sub(freeze.frozen_since))
+				},
+				None => now,
 			}
-			Ok(total_locked)
 		}
 
 		fn calculate_total_locked_amount(
 			schedules: &BoundedVec<VestingScheduleOf<T>, T::MaxVestingSchedules>,
+			now: BlockNumberFor<T>,
 		) -> Result<BalanceOf<T>, DispatchError> {
-			let now = frame_system::Pallet::<T>::block_number();
 			schedules
 				.iter()
-				.try_fold(Zero::zero(), |acc, s| {
+				.try_fold(Zero::zero(), |acc: BalanceOf<T>, s| {
 					let locked = s.locked_amount::<T::BlockNumberToBalance>(now);
 					acc.checked_add(&locked).ok_or(Error::<T>::ArithmeticOverflow)
 				})
@@ -273,7 +1434,7 @@ pub mod pallet {
 		}
 
 		pub fn vested_balance(who: &T::AccountId) -> BalanceOf<T> {
-			let now = frame_system::Pallet::<T>::block_number();
+			let now = Self::vesting_now(who, frame_system::Pallet::<T>::block_number());
 			VestingSchedules::<T>::get(who)
 				.iter()
 				.fold(Zero::zero(), |acc, s| {
@@ -283,7 +1444,7 @@ pub mod pallet {
 		}
 
 		pub fn locked_balance(who: &T::AccountId) -> BalanceOf<T> {
-			let now = frame_system::Pallet::<T>::block_number();
+			let now = Self::vesting_now(who, frame_system::Pallet::<T>::block_number());
 			VestingSchedules::<T>::get(who)
 				.iter()
 				.fold(Zero::zero(), |acc, s| {
@@ -291,5 +1452,15 @@ pub mod pallet {
 					acc.saturating_add(locked)
 				})
 		}
+
+		/// Returns the vesting state of `who` for the runtime API.
+		pub fn vesting_details(who: &T::AccountId) -> VestingDetails<BlockNumberFor<T>, BalanceOf<T>> {
+			VestingDetails {
+				schedules: VestingSchedules::<T>::get(who).into_inner(),
+				vested: Self::vested_balance(who),
+				locked: Self::locked_balance(who),
+				freeze: VestingFreezes::<T>::get(who),
+			}
+		}
 	}
 }
\ No newline at end of file
@@ -0,0 +1,139 @@
+//! Storage migrations for the vesting pallet.
+
+use super::*;
+use core::marker::PhantomData;
+use polkadot_sdk::frame_support::{
+	migrations::VersionedMigration,
+	storage_alias,
+	traits::{LockIdentifier, LockableCurrency, UncheckedOnRuntimeUpgrade},
+	weights::Weight,
+};
+
+/// A vesting schedule as stored before schedules had labels and categories.
+#[derive(Clone, Encode, Decode, MaxEncodedLen, TypeInfo, Debug, PartialEq)]
+pub struct OldVestingSchedule<BlockNumber, Balance> {
+	pub start: BlockNumber,
+	pub period: BlockNumber,
+	pub period_count: u32,
+	pub per_period: Balance,
+}
+
+impl<BlockNumber, Balance> From<OldVestingSchedule<BlockNumber, Balance>>
+	for VestingSchedule<BlockNumber, Balance>
+{
+	fn from(old: OldVestingSchedule<BlockNumber, Balance>) -> Self {
+		VestingSchedule {
+			start: old.start,
+			period: old.period,
+			period_count: old.period_count,
+			per_period: old.per_period,
+			label: None,
+			category: None,
+		}
+	}
+}
+
+type OldSchedulesOf<T> = BoundedVec<
+	OldVestingSchedule<BlockNumberFor<T>, BalanceOf<T>>,
+	<T as Config>::MaxVestingSchedules,
+>;
+
+/// Migration from `LockableCurrency` locks to fungible freezes.
+pub mod v1 {
+	use super::*;
+
+	/// The lock identifier used by the pallet before it moved to fungible freezes.
+	pub const VESTING_ID: LockIdentifier = *b"vesting ";
+
+	/// `VestingSchedules` as stored at storage version 0.
+	#[storage_alias]
+	pub type VestingSchedules<T: Config> = StorageMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		<T as frame_system::Config>::AccountId,
+		OldSchedulesOf<T>,
+		ValueQuery,
+	>;
+
+	/// Removes the legacy `VESTING_ID` lock from every account with vesting schedules and
+	/// places a `FreezeReason::Vesting` freeze for the amount that is still locked.
+	///
+	/// `OldCurrency` is the currency that held the legacy locks, usually the same balances
+	/// pallet that is configured as `Config::Currency`.
+	pub struct InnerMigrateV0ToV1<T, OldCurrency>(PhantomData<(T, OldCurrency)>);
+
+	impl<T, OldCurrency> UncheckedOnRuntimeUpgrade for InnerMigrateV0ToV1<T, OldCurrency>
+	where
+		T: Config,
+		OldCurrency: LockableCurrency<T::AccountId>,
+	{
+		fn on_runtime_upgrade() -> Weight {
+			let now = frame_system::Pallet::<T>::block_number();
+			let reason: T::RuntimeFreezeReason = FreezeReason::Vesting.into();
+			let mut migrated: u64 = 0;
+			for (who, schedules) in VestingSchedules::<T>::iter() {
+				OldCurrency::remove_lock(VESTING_ID, &who);
+				let locked = schedules.into_iter().fold(BalanceOf::<T>::zero(), |acc, old| {
+					let schedule: VestingScheduleOf<T> = old.into();
+					acc.saturating_add(schedule.locked_amount::<T::BlockNumberToBalance>(now))
+				});
+				if !locked.is_zero() {
+					let _ = T::Currency::set_freeze(&reason, &who, locked).defensive();
+				}
+				migrated.saturating_inc();
+			}
+			// Per account: the schedules, the locks and the freezes are read, while the
+			// locks, the freezes and the account data are written.
+			T::DbWeight::get().reads_writes(
+				migrated.saturating_mul(3).saturating_add(1),
+				migrated.saturating_mul(3),
+			)
+		}
+	}
+
+	/// [`InnerMigrateV0ToV1`] wrapped in a [`VersionedMigration`], which ensures it runs only
+	/// once and bumps the storage version to 1.
+	pub type MigrateV0ToV1<T, OldCurrency> = VersionedMigration<
+		0,
+		1,
+		InnerMigrateV0ToV1<T, OldCurrency>,
+		Pallet<T>,
+		<T as frame_system::Config>::DbWeight,
+	>;
+}
+
+/// Migration that adds labels and categories to stored vesting schedules.
+pub mod v2 {
+	use super::*;
+
+	/// Rewrites every native and asset vesting schedule with no label and no category.
+	pub struct InnerMigrateV1ToV2<T>(PhantomData<T>);
+
+	impl<T: Config> UncheckedOnRuntimeUpgrade for InnerMigrateV1ToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let upgrade = |old: OldSchedulesOf<T>| {
+				BoundedVec::truncate_from(old.into_iter().map(Into::into).collect::<Vec<_>>())
+			};
+			let mut migrated: u64 = 0;
+			crate::VestingSchedules::<T>::translate::<OldSchedulesOf<T>, _>(|_, old| {
+				migrated.saturating_inc();
+				Some(upgrade(old))
+			});
+			crate::AssetVestingSchedules::<T>::translate::<OldSchedulesOf<T>, _>(|_, _, old| {
+				migrated.saturating_inc();
+				Some(upgrade(old))
+			});
+			T::DbWeight::get().reads_writes(migrated, migrated)
+		}
+	}
+
+	/// [`InnerMigrateV1ToV2`] wrapped in a [`VersionedMigration`], which ensures it runs only
+	/// once and bumps the storage version to 2.
+	pub type MigrateV1ToV2<T> = VersionedMigration<
+		1,
+		2,
+		InnerMigrateV1ToV2<T>,
+		Pallet<T>,
+		<T as frame_system::Config>::DbWeight,
+	>;
+}
@@ -5,7 +5,8 @@
 use super::*;
 use polkadot_sdk::{frame_support::{
 	construct_runtime, derive_impl, parameter_types,
-	traits::{ConstU32, ConstU64, EnsureOrigin},
+	traits::{AsEnsureOriginWithArg, ConstU32, ConstU64},
+	PalletId,
 }, sp_runtime::traits::ConvertInto};
 
 use polkadot_sdk::{
@@ -30,6 +31,18 @@ impl polkadot_sdk::frame_system::Config for Runtime {
 
 type Balance = u64;
 
+/// A hold placed by another pallet, such as vesting tokens committed to a pool.
+#[derive(
+	Clone, Copy, Encode, Decode, DecodeWithMemTracking, MaxEncodedLen, TypeInfo, Debug, PartialEq, Eq,
+)]
+pub enum TestHoldReason {
+	LockedDeposit,
+}
+
+impl VariantCount for TestHoldReason {
+	const VARIANT_COUNT: u32 = 1;
+}
+
 impl pallet_balances::Config for Runtime {
     type RuntimeEvent = RuntimeEvent;
 	type Balance = Balance;
@@ -40,20 +53,42 @@ impl pallet_balances::Config for Runtime {
 	type MaxReserves = ();
 	type ReserveIdentifier = [u8; 8];
 	type WeightInfo = ();
-	type RuntimeHoldReason = RuntimeHoldReason;
+	type RuntimeHoldReason = TestHoldReason;
 	type RuntimeFreezeReason = RuntimeFreezeReason;
-	type FreezeIdentifier = [u8; 8];
-	type MaxFreezes = ();
+	type FreezeIdentifier = RuntimeFreezeReason;
+	type MaxFreezes = ConstU32<1>;
 	type DoneSlashHandler = ();
 }
 
 
+#[derive_impl(pallet_assets::config_preludes::TestDefaultConfig)]
+impl pallet_assets::Config for Runtime {
+	type Currency = PalletBalances;
+	type CreateOrigin = AsEnsureOriginWithArg<frame_system::EnsureSigned<AccountId>>;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type Freezer = ();
+}
+
+parameter_types! {
+	pub static RequireTransferApproval: bool = false;
+	pub const VestingPalletId: PalletId = PalletId(*b"py/vestn");
+	pub const EthereumClaimPrefix: &'static [u8] = b"Pay to the vesting account:";
+}
+
 impl Config for Runtime {
+	type RuntimeFreezeReason = RuntimeFreezeReason;
 	type Currency = PalletBalances;
 	type MinVestedTransfer = ConstU64<5>;
 	type MaxVestingSchedules = ConstU32<2>;
     type BlockNumberToBalance = ConvertInto;
-
+	type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+	type RequireTransferApproval = RequireTransferApproval;
+	type AssetId = AssetId;
+	type Assets = Assets;
+	type PalletId = VestingPalletId;
+	type EthereumClaimPrefix = EthereumClaimPrefix;
+	type MaxProofLength = ConstU32<16>;
+	type MaxUnlocksPerBlock = ConstU32<1>;
 }
 
 type Block = frame_system::mocking::MockBlock<Runtime>;
@@ -63,6 +98,7 @@ construct_runtime!(
 		System: frame_system,
 		Vesting: vesting,
 		PalletBalances: pallet_balances,
+		Assets: pallet_assets,
 	}
 );
 
@@ -73,6 +109,10 @@ pub const CHARLIE: AccountId = 3;
 pub const ALICE_BALANCE: u64 = 100;
 pub const CHARLIE_BALANCE: u64 = 50;
 
+pub type AssetId = u32;
+pub const ASSET: AssetId = 1;
+pub const ALICE_ASSET_BALANCE: u64 = 100;
+
 #[derive(Default)]
 pub struct ExtBuilder;
 
@@ -89,6 +129,13 @@ impl ExtBuilder {
 		.assimilate_storage(&mut t)
 		.unwrap();
 
+		pallet_assets::GenesisConfig::<Runtime> {
+			assets: vec![(ASSET, ALICE, true, 1)],
+			accounts: vec![(ASSET, ALICE, ALICE_ASSET_BALANCE)],
+			..Default::default()
+		}
+		.assimilate_storage(&mut t)
+		.unwrap();
 
 		t.into()
 	}
@@ -0,0 +1,20 @@
+//! Runtime API definition for the vesting pallet.
+
+use super::*;
+use codec::Codec;
+
+polkadot_sdk::sp_api::decl_runtime_apis! {
+	/// Queries the vesting state of accounts.
+	pub trait VestingApi<AccountId, Balance, BlockNumber>
+	where
+		AccountId: Codec,
+		Balance: Codec,
+		BlockNumber: Codec,
+	{
+		/// Returns the schedules, the vested and locked amounts and the freeze of `who`.
+		fn vesting_details(who: AccountId) -> VestingDetails<BlockNumber, Balance>;
+
+		/// Returns the locked and vested totals of the native schedules in `category`.
+		fn category_totals(category: u32) -> CategoryTotals<Balance>;
+	}
+}
@@ -1,9 +1,17 @@
 #![cfg(test)]
 
 use super::*;
-use polkadot_sdk::frame_support::{assert_noop, assert_ok};
+use polkadot_sdk::frame_support::{
+	assert_noop, assert_ok,
+	traits::fungible::{InspectFreeze, MutateHold},
+};
 use mock::*;
 
+/// Amount frozen on `who` by the vesting pallet.
+fn vesting_frozen(who: &AccountId) -> u64 {
+	PalletBalances::balance_frozen(&FreezeReason::Vesting.into(), who)
+}
+
 /// Vested transfer end‑to‑end flow with intermediate claims.
 #[test]
 fn vested_transfer_works() {
@@ -17,6 +25,8 @@ fn vested_transfer_works() {
 			period: 5,
 			period_count: 2,
 			per_period: 10,
+			label: None,
+			category: None,
 		};
 
 		// Perform vested transfer from Alice to Bob.
@@ -25,17 +35,19 @@ fn vested_transfer_works() {
 		// One schedule stored for Bob and full amount locked.
 		assert_eq!(Vesting::vesting_schedules(&BOB).len(), 1);
 		assert_eq!(Vesting::locked_balance(&BOB), 20);
+		assert_eq!(vesting_frozen(&BOB), 20);
 
 		// Move one period forward and claim.
 		System::set_block_number(15);
 		assert_ok!(Vesting::claim(RuntimeOrigin::signed(BOB)));
 		assert_eq!(Vesting::locked_balance(&BOB), 10);
+		assert_eq!(vesting_frozen(&BOB), 10);
 
 		// Move past the final period and claim again to unlock everything.
 		System::set_block_number(25);
 		assert_ok!(Vesting::claim(RuntimeOrigin::signed(BOB)));
 		assert_eq!(Vesting::locked_balance(&BOB), 0);
-		assert_eq!(PalletBalances::locks(&BOB).len(), 0);
+		assert_eq!(vesting_frozen(&BOB), 0);
 	});
 }
 
@@ -43,13 +55,13 @@ fn vested_transfer_works() {
 #[test]
 fn vested_transfer_fails_if_zero_period_or_count() {
     ExtBuilder::build().execute_with(|| { 
-		let zero_period = VestingSchedule { start: 10, period: 0, period_count: 1, per_period: 10 };
+		let zero_period = VestingSchedule { start: 10, period: 0, period_count: 1, per_period: 10, label: None, category: None };
 		assert_noop!(
 			Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, zero_period),
 			Error::<Runtime>::ZeroVestingPeriod
 		);
 
-		let zero_count = VestingSchedule { start: 10, period: 5, period_count: 0, per_period: 10 };
+		let zero_count = VestingSchedule { start: 10, period: 5, period_count: 0, per_period: 10, label: None, category: None };
 		assert_noop!(
 			Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, zero_count),
 			Error::<Runtime>::ZeroVestingPeriodCount
@@ -61,7 +73,7 @@ fn vested_transfer_fails_if_zero_period_or_count() {
 #[test]
 fn claim_works() {
 	ExtBuilder::build().execute_with(|| {
-		let schedule = VestingSchedule { start: 5, period: 5, period_count: 3, per_period: 10 };
+		let schedule = VestingSchedule { start: 5, period: 5, period_count: 3, per_period: 10, label: None, category: None };
 		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, schedule));
 
 		// Fast‑forward beyond full vesting.
@@ -69,7 +81,7 @@ fn claim_works() {
 		assert_ok!(Vesting::claim(RuntimeOrigin::signed(BOB)));
 
 		assert_eq!(Vesting::locked_balance(&BOB), 0);
-		assert_eq!(PalletBalances::locks(&BOB).len(), 0);
+		assert_eq!(vesting_frozen(&BOB), 0);
 	});
 }
 
@@ -77,8 +89,8 @@ fn claim_works() {
 #[test]
 fn update_vesting_schedules_works() {
 	ExtBuilder::build().execute_with(|| {
-		let schedule1 = VestingSchedule { start: 10, period: 10, period_count: 3, per_period: 10 };
-		let schedule2 = VestingSchedule { start: 20, period: 10, period_count: 2, per_period: 10 };
+		let schedule1 = VestingSchedule { start: 10, period: 10, period_count: 3, per_period: 10, label: None, category: None };
+		let schedule2 = VestingSchedule { start: 20, period: 10, period_count: 2, per_period: 10, label: None, category: None };
 		let schedules = vec![schedule1.clone(), schedule2.clone()];
 
 		assert_ok!(Vesting::update_vesting_schedules(RuntimeOrigin::root(), CHARLIE, schedules));
@@ -92,12 +104,68 @@ fn update_vesting_schedules_works() {
 	});
 }
 
+/// Schedule management calls require `AdminOrigin`.
+#[test]
+fn update_calls_require_admin_origin() {
+	ExtBuilder::build().execute_with(|| {
+		let schedule = VestingSchedule { start: 10, period: 10, period_count: 3, per_period: 10, label: None, category: None };
+		assert_noop!(
+			Vesting::update_vesting_schedules(
+				RuntimeOrigin::signed(ALICE),
+				CHARLIE,
+				vec![schedule.clone()]
+			),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Vesting::update_vesting_schedules(RuntimeOrigin::root(), CHARLIE, vec![schedule.clone()]));
+		assert_noop!(
+			Vesting::update_vesting_schedule(RuntimeOrigin::signed(ALICE), CHARLIE, 0, schedule),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Vesting::force_remove_vesting_schedule(RuntimeOrigin::signed(ALICE), CHARLIE, 0),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+/// Update calls validate schedules like `vested_transfer` does.
+#[test]
+fn update_calls_validate_schedules() {
+	ExtBuilder::build().execute_with(|| {
+		System::set_block_number(5);
+		let valid = VestingSchedule { start: 10, period: 10, period_count: 3, per_period: 10, label: None, category: None };
+		let zero_period = VestingSchedule { start: 10, period: 0, period_count: 3, per_period: 10, label: None, category: None };
+		let started = VestingSchedule { start: 5, period: 10, period_count: 3, per_period: 10, label: None, category: None };
+		let too_small = VestingSchedule { start: 10, period: 10, period_count: 1, per_period: 1, label: None, category: None };
+
+		assert_noop!(
+			Vesting::update_vesting_schedules(RuntimeOrigin::root(), CHARLIE, vec![valid.clone(), zero_period]),
+			Error::<Runtime>::ZeroVestingPeriod
+		);
+		assert_noop!(
+			Vesting::update_vesting_schedules(RuntimeOrigin::root(), CHARLIE, vec![started.clone()]),
+			Error::<Runtime>::InvalidVestingStart
+		);
+
+		assert_ok!(Vesting::update_vesting_schedules(RuntimeOrigin::root(), CHARLIE, vec![valid]));
+		assert_noop!(
+			Vesting::update_vesting_schedule(RuntimeOrigin::root(), CHARLIE, 0, started),
+			Error::<Runtime>::InvalidVestingStart
+		);
+		assert_noop!(
+			Vesting::update_vesting_schedule(RuntimeOrigin::root(), CHARLIE, 0, too_small),
+			Error::<Runtime>::AmountLow
+		);
+	});
+}
+
 /// Claims on multiple schedules unlock proportionally over time.
 #[test]
 fn multiple_vesting_schedule_claim_works() {
 	ExtBuilder::build().execute_with(|| {
-		let schedule1 = VestingSchedule { start: 5, period: 5, period_count: 2, per_period: 10 }; // total 20
-		let schedule2 = VestingSchedule { start: 10, period: 5, period_count: 2, per_period: 10 }; // total 20
+		let schedule1 = VestingSchedule { start: 5, period: 5, period_count: 2, per_period: 10, label: None, category: None }; // total 20
+		let schedule2 = VestingSchedule { start: 10, period: 5, period_count: 2, per_period: 10, label: None, category: None }; // total 20
 
 		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, schedule1));
 		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, schedule2));
@@ -114,4 +182,667 @@ fn multiple_vesting_schedule_claim_works() {
 		assert_ok!(Vesting::claim(RuntimeOrigin::signed(BOB)));
 		assert_eq!(Vesting::locked_balance(&BOB), 0);
 	});
-}
\ No newline at end of file
+}
+
+/// Nothing vests while an account is frozen and its schedules resume after a thaw.
+#[test]
+fn freeze_and_thaw_vesting_works() {
+	ExtBuilder::build().execute_with(|| {
+		System::set_block_number(1);
+		let schedule = VestingSchedule { start: 10, period: 5, period_count: 2, per_period: 10, label: None, category: None };
+		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, schedule));
+
+		System::set_block_number(15);
+		assert_noop!(
+			Vesting::freeze_vesting(RuntimeOrigin::signed(ALICE), BOB, None),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Vesting::freeze_vesting(RuntimeOrigin::root(), BOB, None));
+		assert_eq!(vesting_frozen(&BOB), 10);
+		assert_noop!(
+			Vesting::freeze_vesting(RuntimeOrigin::root(), BOB, None),
+			Error::<Runtime>::AlreadyFrozen
+		);
+
+		// Claims during the freeze unlock nothing.
+		System::set_block_number(40);
+		assert_ok!(Vesting::claim(RuntimeOrigin::signed(BOB)));
+		assert_eq!(Vesting::locked_balance(&BOB), 10);
+		assert_eq!(vesting_frozen(&BOB), 10);
+
+		// Schedules cannot be replaced while the freeze lasts.
+		let replacement = VestingSchedule { start: 50, period: 5, period_count: 2, per_period: 10, label: None, category: None };
+		assert_noop!(
+			Vesting::update_vesting_schedules(RuntimeOrigin::root(), BOB, vec![replacement.clone()]),
+			Error::<Runtime>::VestingFrozen
+		);
+		assert_noop!(
+			Vesting::update_vesting_schedule(RuntimeOrigin::root(), BOB, 0, replacement),
+			Error::<Runtime>::VestingFrozen
+		);
+		assert_eq!(
+			Vesting::vesting_details(&BOB).freeze,
+			Some(VestingFreeze { frozen_since: 15, frozen_until: None })
+		);
+
+		// Thawing after 25 frozen blocks moves the schedule start from 10 to 35.
+		assert_ok!(Vesting::thaw_vesting(RuntimeOrigin::root(), BOB));
+		assert_eq!(Vesting::vesting_schedules(&BOB)[0].start, 35);
+		assert_eq!(Vesting::vesting_details(&BOB).freeze, None);
+		assert_noop!(Vesting::thaw_vesting(RuntimeOrigin::root(), BOB), Error::<Runtime>::NotFrozen);
+
+		System::set_block_number(45);
+		assert_ok!(Vesting::claim(RuntimeOrigin::signed(BOB)));
+		assert_eq!(vesting_frozen(&BOB), 0);
+	});
+}
+
+/// A freeze with an end block lifts itself once that block is reached.
+#[test]
+fn freeze_vesting_until_block_expires() {
+	ExtBuilder::build().execute_with(|| {
+		System::set_block_number(1);
+		let schedule = VestingSchedule { start: 10, period: 5, period_count: 2, per_period: 10, label: None, category: None };
+		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, schedule.clone()));
+		assert_noop!(
+			Vesting::freeze_vesting(RuntimeOrigin::root(), BOB, Some(1)),
+			Error::<Runtime>::InvalidFreezeEnd
+		);
+		assert_ok!(Vesting::freeze_vesting(RuntimeOrigin::root(), BOB, Some(6)));
+
+		// Frozen accounts cannot receive new vested transfers.
+		assert_noop!(
+			Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, schedule),
+			Error::<Runtime>::VestingFrozen
+		);
+
+		// Five frozen blocks delay the first period from block 15 to block 20.
+		System::set_block_number(19);
+		assert_eq!(Vesting::locked_balance(&BOB), 20);
+		System::set_block_number(20);
+		assert_ok!(Vesting::claim(RuntimeOrigin::signed(BOB)));
+		assert_eq!(vesting_frozen(&BOB), 10);
+		assert_eq!(Vesting::vesting_freezes(&BOB), None);
+		assert_eq!(Vesting::vesting_schedules(&BOB)[0].start, 15);
+	});
+}
+
+/// A beneficiary can move a schedule and its locked balance to another account.
+#[test]
+fn transfer_schedule_works() {
+	ExtBuilder::build().execute_with(|| {
+		System::set_block_number(1);
+		let schedule = VestingSchedule { start: 10, period: 5, period_count: 2, per_period: 10, label: None, category: None };
+		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, schedule.clone()));
+
+		System::set_block_number(15);
+		assert_noop!(
+			Vesting::transfer_schedule(RuntimeOrigin::signed(BOB), 1, CHARLIE),
+			Error::<Runtime>::InvalidVestingIndex
+		);
+		assert_ok!(Vesting::transfer_schedule(RuntimeOrigin::signed(BOB), 0, CHARLIE));
+
+		// The vested half stays with Bob, the locked half moves to Charlie.
+		assert!(Vesting::vesting_schedules(&BOB).is_empty());
+		assert_eq!(vesting_frozen(&BOB), 0);
+		assert_eq!(PalletBalances::free_balance(&BOB), 10);
+		assert_eq!(Vesting::vesting_schedules(&CHARLIE).to_vec(), vec![schedule]);
+		assert_eq!(vesting_frozen(&CHARLIE), 10);
+		assert_eq!(PalletBalances::free_balance(&CHARLIE), CHARLIE_BALANCE + 10);
+	});
+}
+
+/// The locked balance of a schedule cannot be moved while it is on hold, such as vesting tokens
+/// committed to native pools with `deposit_locked`.
+#[test]
+fn transfer_schedule_fails_if_locked_balance_is_on_hold() {
+	ExtBuilder::build().execute_with(|| {
+		System::set_block_number(1);
+		let schedule = VestingSchedule { start: 10, period: 5, period_count: 2, per_period: 10, label: None, category: None };
+		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, schedule.clone()));
+		assert_ok!(PalletBalances::hold(&TestHoldReason::LockedDeposit, &BOB, 15));
+
+		System::set_block_number(15);
+		assert_noop!(
+			Vesting::transfer_schedule(RuntimeOrigin::signed(BOB), 0, CHARLIE),
+			Error::<Runtime>::LockedBalanceOnHold
+		);
+
+		// Once the hold is released the schedule moves with its locked balance.
+		assert_ok!(PalletBalances::release(
+			&TestHoldReason::LockedDeposit,
+			&BOB,
+			15,
+			Precision::Exact
+		));
+		assert_ok!(Vesting::transfer_schedule(RuntimeOrigin::signed(BOB), 0, CHARLIE));
+		assert_eq!(Vesting::vesting_schedules(&CHARLIE).to_vec(), vec![schedule]);
+		assert_eq!(vesting_frozen(&CHARLIE), 10);
+	});
+}
+
+/// Transfers respect the recipient's schedule limit.
+#[test]
+fn transfer_schedule_fails_if_recipient_is_full() {
+	ExtBuilder::build().execute_with(|| {
+		System::set_block_number(1);
+		let schedule = VestingSchedule { start: 10, period: 5, period_count: 2, per_period: 10, label: None, category: None };
+		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, schedule.clone()));
+		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), CHARLIE, schedule.clone()));
+		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), CHARLIE, schedule));
+
+		assert_noop!(
+			Vesting::transfer_schedule(RuntimeOrigin::signed(BOB), 0, CHARLIE),
+			Error::<Runtime>::TooManyVestingSchedules
+		);
+	});
+}
+
+/// With `RequireTransferApproval` set, only approved transfers go through.
+#[test]
+fn transfer_schedule_requires_approval_when_configured() {
+	ExtBuilder::build().execute_with(|| {
+		RequireTransferApproval::set(true);
+		System::set_block_number(1);
+		let schedule = VestingSchedule { start: 10, period: 5, period_count: 2, per_period: 10, label: None, category: None };
+		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, schedule));
+
+		assert_noop!(
+			Vesting::transfer_schedule(RuntimeOrigin::signed(BOB), 0, CHARLIE),
+			Error::<Runtime>::TransferNotApproved
+		);
+		// Only Alice, who granted the schedule, can approve its transfer.
+		assert_eq!(Vesting::schedule_grantor(&BOB, 0), Some(ALICE));
+		assert_noop!(
+			Vesting::approve_schedule_transfer(RuntimeOrigin::signed(BOB), BOB, 0, CHARLIE),
+			Error::<Runtime>::NotGrantor
+		);
+		assert_noop!(
+			Vesting::approve_schedule_transfer(RuntimeOrigin::root(), BOB, 0, CHARLIE),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Vesting::approve_schedule_transfer(RuntimeOrigin::signed(ALICE), BOB, 0, CHARLIE));
+
+		// The approval names the recipient.
+		assert_noop!(
+			Vesting::transfer_schedule(RuntimeOrigin::signed(BOB), 0, ALICE),
+			Error::<Runtime>::TransferNotApproved
+		);
+		assert_ok!(Vesting::transfer_schedule(RuntimeOrigin::signed(BOB), 0, CHARLIE));
+		assert_eq!(Vesting::transfer_approvals(&BOB), None);
+		assert_eq!(vesting_frozen(&CHARLIE), 20);
+		assert_eq!(Vesting::schedule_grantor(&CHARLIE, 0), Some(ALICE));
+	});
+}
+
+/// Asset schedules escrow their total and release it to the beneficiary as it vests.
+#[test]
+fn vested_transfer_asset_works() {
+	ExtBuilder::build().execute_with(|| {
+		System::set_block_number(1);
+		let schedule = VestingSchedule { start: 10, period: 5, period_count: 2, per_period: 10, label: None, category: None };
+		assert_ok!(Vesting::vested_transfer_asset(RuntimeOrigin::signed(ALICE), ASSET, BOB, schedule));
+
+		let escrow = Vesting::escrow_account();
+		assert_eq!(Assets::balance(ASSET, ALICE), ALICE_ASSET_BALANCE - 20);
+		assert_eq!(Assets::balance(ASSET, escrow), 20);
+		assert_eq!(Vesting::asset_escrowed(BOB, ASSET), 20);
+		assert_noop!(Vesting::claim_asset(RuntimeOrigin::signed(BOB), ASSET), Error::<Runtime>::NotVested);
+
+		System::set_block_number(15);
+		assert_ok!(Vesting::claim_asset(RuntimeOrigin::signed(BOB), ASSET));
+		assert_eq!(Assets::balance(ASSET, BOB), 10);
+		assert_eq!(Vesting::asset_escrowed(BOB, ASSET), 10);
+
+		System::set_block_number(25);
+		assert_ok!(Vesting::claim_asset(RuntimeOrigin::signed(BOB), ASSET));
+		assert_eq!(Assets::balance(ASSET, BOB), 20);
+		assert_eq!(Assets::balance(ASSET, escrow), 0);
+		assert!(Vesting::asset_vesting_schedules(BOB, ASSET).is_empty());
+		assert_eq!(Vesting::asset_escrowed(BOB, ASSET), 0);
+	});
+}
+
+/// Asset vested transfers use the per-asset minimum.
+#[test]
+fn vested_transfer_asset_respects_asset_minimum() {
+	ExtBuilder::build().execute_with(|| {
+		let schedule = VestingSchedule { start: 10, period: 5, period_count: 2, per_period: 10, label: None, category: None };
+		assert_noop!(
+			Vesting::set_asset_min_vested_transfer(RuntimeOrigin::signed(ALICE), ASSET, 50),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Vesting::set_asset_min_vested_transfer(RuntimeOrigin::root(), ASSET, 50));
+		assert_noop!(
+			Vesting::vested_transfer_asset(RuntimeOrigin::signed(ALICE), ASSET, BOB, schedule),
+			Error::<Runtime>::AmountLow
+		);
+	});
+}
+
+/// Hashes an airdrop leaf or node pair the way the pallet does.
+fn airdrop_hash<E: Encode>(value: &E) -> <Runtime as frame_system::Config>::Hash {
+	<Runtime as frame_system::Config>::Hashing::hash_of(value)
+}
+
+/// Opens an airdrop for Bob (30, of which 20 vest) and Charlie (10) and returns Bob's claim.
+fn setup_airdrop() -> (
+	u64,
+	VestingSchedule<u64, u64>,
+	BoundedVec<<Runtime as frame_system::Config>::Hash, ConstU32<16>>,
+) {
+	let schedule = VestingSchedule { start: 10, period: 5, period_count: 2, per_period: 10, label: None, category: None };
+	let bob_leaf = airdrop_hash(&(BOB, 30u64, schedule.clone()));
+	let charlie_schedule = VestingSchedule { start: 10, period: 5, period_count: 1, per_period: 5, label: None, category: None };
+	let charlie_leaf = airdrop_hash(&(CHARLIE, 10u64, charlie_schedule));
+	let root = if bob_leaf <= charlie_leaf {
+		airdrop_hash(&(bob_leaf, charlie_leaf))
+	} else {
+		airdrop_hash(&(charlie_leaf, bob_leaf))
+	};
+
+	assert_ok!(Vesting::create_airdrop(RuntimeOrigin::root(), root));
+	assert_ok!(PalletBalances::transfer_allow_death(
+		RuntimeOrigin::signed(ALICE),
+		Vesting::airdrop_account(0),
+		50
+	));
+	(30, schedule, BoundedVec::truncate_from(vec![charlie_leaf]))
+}
+
+/// Airdrop claims pay from the pot, create the schedule and cannot be repeated.
+#[test]
+fn claim_airdrop_works() {
+	ExtBuilder::build().execute_with(|| {
+		System::set_block_number(1);
+		let (amount, schedule, proof) = setup_airdrop();
+
+		assert_noop!(
+			Vesting::claim_airdrop(RuntimeOrigin::signed(BOB), 0, BOB, amount, schedule.clone(), proof.clone()),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Vesting::claim_airdrop(RuntimeOrigin::none(), 0, BOB, amount + 1, schedule.clone(), proof.clone()),
+			Error::<Runtime>::InvalidProof
+		);
+		assert_ok!(Vesting::claim_airdrop(RuntimeOrigin::none(), 0, BOB, amount, schedule.clone(), proof.clone()));
+
+		assert_eq!(PalletBalances::free_balance(&BOB), 30);
+		assert_eq!(vesting_frozen(&BOB), 20);
+		assert_eq!(PalletBalances::free_balance(&Vesting::airdrop_account(0)), 20);
+		assert_noop!(
+			Vesting::claim_airdrop(RuntimeOrigin::none(), 0, BOB, amount, schedule, proof),
+			Error::<Runtime>::AlreadyClaimed
+		);
+	});
+}
+
+/// Only valid, unclaimed airdrop claims enter the transaction pool.
+#[test]
+fn claim_airdrop_validate_unsigned_works() {
+	use polkadot_sdk::sp_runtime::transaction_validity::TransactionSource;
+
+	ExtBuilder::build().execute_with(|| {
+		System::set_block_number(1);
+		let (amount, schedule, proof) = setup_airdrop();
+
+		let call = Call::claim_airdrop {
+			airdrop_id: 0,
+			who: BOB,
+			amount,
+			schedule: schedule.clone(),
+			proof: proof.clone(),
+		};
+		assert!(Vesting::validate_unsigned(TransactionSource::External, &call).is_ok());
+
+		let forged = Call::claim_airdrop { airdrop_id: 0, who: ALICE, amount, schedule, proof };
+		assert_eq!(
+			Vesting::validate_unsigned(TransactionSource::External, &forged),
+			InvalidTransaction::BadProof.into()
+		);
+
+		// Claims that would fail in dispatch are rejected as well.
+		assert_ok!(Vesting::freeze_vesting(RuntimeOrigin::root(), BOB, None));
+		assert_eq!(
+			Vesting::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Call.into()
+		);
+		assert_ok!(Vesting::thaw_vesting(RuntimeOrigin::root(), BOB));
+		assert_ok!(Vesting::close_airdrop(RuntimeOrigin::root(), 0, ALICE));
+		assert_eq!(
+			Vesting::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Stale.into()
+		);
+		assert_ne!(Vesting::airdrop_account(0), Vesting::escrow_account());
+	});
+}
+
+/// Closing an airdrop sweeps the pot and rejects later claims.
+#[test]
+fn close_airdrop_works() {
+	ExtBuilder::build().execute_with(|| {
+		System::set_block_number(1);
+		let (amount, schedule, proof) = setup_airdrop();
+
+		assert_noop!(
+			Vesting::close_airdrop(RuntimeOrigin::signed(ALICE), 0, ALICE),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Vesting::close_airdrop(RuntimeOrigin::root(), 0, CHARLIE));
+		assert_eq!(PalletBalances::free_balance(&CHARLIE), CHARLIE_BALANCE + 50);
+		assert_noop!(
+			Vesting::claim_airdrop(RuntimeOrigin::none(), 0, BOB, amount, schedule, proof),
+			Error::<Runtime>::UnknownAirdrop
+		);
+	});
+}
+
+/// Signs `dest` the way `personal_sign` would for an Ethereum claim.
+fn ethereum_sign(pair: &polkadot_sdk::sp_core::ecdsa::Pair, dest: &AccountId) -> EcdsaSignature {
+	let message = dest.using_encoded(|data| {
+		ethereum::ethereum_signable_message(EthereumClaimPrefix::get(), data)
+	});
+	EcdsaSignature(pair.sign_prehashed(&polkadot_sdk::sp_io::hashing::keccak_256(&message)).0)
+}
+
+/// Ethereum holders claim their registered amount and vesting schedule with a signature.
+#[test]
+fn claim_ethereum_works() {
+	use polkadot_sdk::sp_core::Pair;
+
+	ExtBuilder::build().execute_with(|| {
+		System::set_block_number(1);
+		let pair = polkadot_sdk::sp_core::ecdsa::Pair::from_seed(&[7u8; 32]);
+		let signature = ethereum_sign(&pair, &BOB);
+		let address = BOB
+			.using_encoded(|data| {
+				ethereum::eth_recover(&signature, EthereumClaimPrefix::get(), data)
+			})
+			.unwrap();
+
+		let schedule = VestingSchedule { start: 10, period: 5, period_count: 2, per_period: 10, label: None, category: None };
+		assert_noop!(
+			Vesting::register_ethereum_claim(RuntimeOrigin::root(), address, 10, Some(schedule.clone())),
+			Error::<Runtime>::ScheduleExceedsAmount
+		);
+		assert_ok!(Vesting::register_ethereum_claim(RuntimeOrigin::root(), address, 30, Some(schedule)));
+
+		// Claims fail while the claims account cannot pay or the destination is frozen.
+		assert_noop!(
+			Vesting::claim_ethereum(RuntimeOrigin::none(), BOB, signature.clone()),
+			Error::<Runtime>::InsufficientPotBalance
+		);
+		assert_ok!(PalletBalances::transfer_allow_death(
+			RuntimeOrigin::signed(ALICE),
+			Vesting::ethereum_claims_account(),
+			50
+		));
+		assert_ok!(Vesting::freeze_vesting(RuntimeOrigin::root(), BOB, None));
+		assert_noop!(
+			Vesting::claim_ethereum(RuntimeOrigin::none(), BOB, signature.clone()),
+			Error::<Runtime>::VestingFrozen
+		);
+		assert_ok!(Vesting::thaw_vesting(RuntimeOrigin::root(), BOB));
+
+		// A signature over another destination does not match the claim.
+		assert_noop!(
+			Vesting::claim_ethereum(RuntimeOrigin::none(), CHARLIE, signature.clone()),
+			Error::<Runtime>::InvalidEthereumSignature
+		);
+		assert_ok!(Vesting::claim_ethereum(RuntimeOrigin::none(), BOB, signature.clone()));
+		assert_eq!(PalletBalances::free_balance(&BOB), 30);
+		assert_eq!(vesting_frozen(&BOB), 20);
+		assert_eq!(Vesting::ethereum_claims(address), None);
+
+		// The claim can only be made once.
+		assert_noop!(
+			Vesting::claim_ethereum(RuntimeOrigin::none(), BOB, signature),
+			Error::<Runtime>::InvalidEthereumSignature
+		);
+	});
+}
+
+/// The v1 migration replaces the legacy `VESTING_ID` lock with a vesting freeze.
+#[test]
+fn migrate_locks_to_freezes_works() {
+	use polkadot_sdk::frame_support::traits::{
+		LockableCurrency, UncheckedOnRuntimeUpgrade, WithdrawReasons,
+	};
+
+	ExtBuilder::build().execute_with(|| {
+		let schedule =
+			migrations::OldVestingSchedule { start: 10, period: 5, period_count: 2, per_period: 10 };
+		migrations::v1::VestingSchedules::<Runtime>::insert(
+			CHARLIE,
+			BoundedVec::truncate_from(vec![schedule]),
+		);
+		<PalletBalances as LockableCurrency<AccountId>>::set_lock(
+			migrations::v1::VESTING_ID,
+			&CHARLIE,
+			20,
+			WithdrawReasons::all(),
+		);
+
+		migrations::v1::InnerMigrateV0ToV1::<Runtime, PalletBalances>::on_runtime_upgrade();
+
+		assert!(PalletBalances::locks(&CHARLIE).is_empty());
+		assert_eq!(vesting_frozen(&CHARLIE), 20);
+	});
+}
+
+/// The v2 migration adds empty labels and categories to stored schedules.
+#[test]
+fn migrate_schedules_to_v2_works() {
+	use polkadot_sdk::frame_support::traits::UncheckedOnRuntimeUpgrade;
+
+	ExtBuilder::build().execute_with(|| {
+		let old =
+			migrations::OldVestingSchedule { start: 10, period: 5, period_count: 2, per_period: 10 };
+		migrations::v1::VestingSchedules::<Runtime>::insert(
+			CHARLIE,
+			BoundedVec::truncate_from(vec![old]),
+		);
+
+		migrations::v2::InnerMigrateV1ToV2::<Runtime>::on_runtime_upgrade();
+
+		assert_eq!(
+			Vesting::vesting_schedules(&CHARLIE).to_vec(),
+			vec![VestingSchedule {
+				start: 10,
+				period: 5,
+				period_count: 2,
+				per_period: 10,
+				label: None,
+				category: None,
+			}]
+		);
+	});
+}
+
+/// Category totals follow schedules as they vest and move between accounts.
+#[test]
+fn category_totals_track_schedules() {
+	ExtBuilder::build().execute_with(|| {
+		System::set_block_number(1);
+		let team = VestingSchedule {
+			start: 10,
+			period: 5,
+			period_count: 2,
+			per_period: 10,
+			label: Some(BoundedVec::truncate_from(b"team grant".to_vec())),
+			category: Some(1),
+		};
+		let unlabeled = VestingSchedule { start: 10, period: 5, period_count: 1, per_period: 10, label: None, category: None };
+		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, team.clone()));
+		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, unlabeled));
+		assert_eq!(Vesting::category_totals(1), CategoryTotals { locked: 20, vested: 0 });
+		assert_eq!(Vesting::vesting_details(&BOB).schedules[0], team);
+
+		System::set_block_number(15);
+		assert_ok!(Vesting::claim(RuntimeOrigin::signed(BOB)));
+		assert_eq!(Vesting::category_totals(1), CategoryTotals { locked: 10, vested: 10 });
+
+		assert_ok!(Vesting::transfer_schedule(RuntimeOrigin::signed(BOB), 0, CHARLIE));
+		assert_eq!(Vesting::category_totals(1), CategoryTotals { locked: 10, vested: 10 });
+
+		assert_ok!(Vesting::force_remove_vesting_schedule(RuntimeOrigin::root(), CHARLIE, 0));
+		assert_eq!(Vesting::category_totals(1), CategoryTotals::default());
+	});
+}
+
+/// Vested funds are unlocked in `on_initialize` without a claim, within the per-block budget.
+#[test]
+fn on_initialize_unlocks_due_accounts() {
+	ExtBuilder::build().execute_with(|| {
+		System::set_block_number(1);
+		let schedule = VestingSchedule { start: 10, period: 5, period_count: 2, per_period: 10, label: None, category: None };
+		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, schedule.clone()));
+		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), CHARLIE, schedule));
+
+		// Only one account fits in block 15, the other one is pushed to block 16.
+		assert_eq!(Vesting::next_unlock(&BOB), Some(15));
+		assert_eq!(Vesting::next_unlock(&CHARLIE), Some(16));
+
+		System::set_block_number(15);
+		Vesting::on_initialize(15);
+		assert_eq!(vesting_frozen(&BOB), 10);
+		assert_eq!(vesting_frozen(&CHARLIE), 20);
+		assert_eq!(Vesting::next_unlock(&BOB), Some(20));
+
+		System::set_block_number(16);
+		Vesting::on_initialize(16);
+		assert_eq!(vesting_frozen(&CHARLIE), 10);
+
+		System::set_block_number(20);
+		Vesting::on_initialize(20);
+		assert_eq!(vesting_frozen(&BOB), 0);
+		assert_eq!(Vesting::next_unlock(&BOB), None);
+	});
+}
+
+/// Rescheduled accounts leave no stale queue entries and a full queue is skipped.
+#[test]
+fn unlock_queue_drops_stale_entries() {
+	ExtBuilder::build().execute_with(|| {
+		System::set_block_number(1);
+		let schedule = VestingSchedule { start: 10, period: 5, period_count: 2, per_period: 10, label: None, category: None };
+		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, schedule.clone()));
+		assert_eq!(Vesting::next_unlock(&BOB), Some(15));
+
+		// Removing the schedule also removes the queue entry, freeing block 15.
+		assert_ok!(Vesting::force_remove_vesting_schedule(RuntimeOrigin::root(), BOB, 0));
+		assert_eq!(Vesting::next_unlock(&BOB), None);
+		assert!(UnlockQueue::<Runtime>::get(15).is_empty());
+
+		// Entries left behind by accounts that moved elsewhere are dropped when a block is full.
+		UnlockQueue::<Runtime>::insert(15, BoundedVec::truncate_from(vec![100]));
+		NextUnlock::<Runtime>::insert(100, 20);
+		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), CHARLIE, schedule.clone()));
+		assert_eq!(Vesting::next_unlock(&CHARLIE), Some(15));
+
+		// With every probed block taken, the schedule is created without queueing a refresh.
+		let blocks = (15..25).chain((0..16).map(|shift| 15 + 10 * 2u64.pow(shift)));
+		for (i, block) in blocks.enumerate() {
+			let who = 200 + i as AccountId;
+			UnlockQueue::<Runtime>::insert(block, BoundedVec::truncate_from(vec![who]));
+			NextUnlock::<Runtime>::insert(who, block);
+		}
+		assert_ok!(Vesting::vested_transfer(RuntimeOrigin::signed(ALICE), BOB, schedule));
+		assert_eq!(vesting_frozen(&BOB), 20);
+		assert_eq!(Vesting::next_unlock(&BOB), None);
+	});
+}
+
+/// Repeated payouts under the same label are merged into one schedule.
+#[test]
+fn vested_transfer_merging_works() {
+	ExtBuilder::build().execute_with(|| {
+		let label: ScheduleLabel = BoundedVec::truncate_from(b"pool-rewards".to_vec());
+		System::set_block_number(10);
+
+		// Below `MinVestedTransfer` and with nothing to merge into, the amount is not locked.
+		assert_ok!(Vesting::vested_transfer_merging(&ALICE, &BOB, 4, 5, 2, label.clone()));
+		assert!(Vesting::vesting_schedules(&BOB).is_empty());
+		assert_eq!(PalletBalances::free_balance(&BOB), 4);
+
+		assert_ok!(Vesting::vested_transfer_merging(&ALICE, &BOB, 20, 5, 2, label.clone()));
+		assert_eq!(vesting_frozen(&BOB), 20);
+
+		// The new amount is spread over the periods left, what does not divide into them vests
+		// with the last one.
+		System::set_block_number(12);
+		assert_ok!(Vesting::vested_transfer_merging(&ALICE, &BOB, 3, 5, 2, label.clone()));
+		assert_eq!(
+			Vesting::vesting_schedules(&BOB).to_vec(),
+			vec![
+				VestingSchedule {
+					start: 10,
+					period: 5,
+					period_count: 2,
+					per_period: 11,
+					label: Some(label.clone()),
+					category: None,
+				},
+				VestingSchedule {
+					start: 15,
+					period: 5,
+					period_count: 1,
+					per_period: 1,
+					label: Some(label.clone()),
+					category: None,
+				},
+			]
+		);
+		assert_eq!(vesting_frozen(&BOB), 23);
+		assert_eq!(ScheduleGrantors::<Runtime>::get(&BOB).to_vec(), vec![Some(ALICE), Some(ALICE)]);
+
+		// Half of the first payout has vested, the rest still ends at block 20.
+		System::set_block_number(15);
+		assert_ok!(Vesting::vested_transfer_merging(&ALICE, &BOB, 2, 5, 2, label.clone()));
+		assert_eq!(
+			Vesting::vesting_schedules(&BOB).to_vec(),
+			vec![
+				VestingSchedule {
+					start: 15,
+					period: 5,
+					period_count: 1,
+					per_period: 13,
+					label: Some(label.clone()),
+					category: None,
+				},
+				VestingSchedule {
+					start: 15,
+					period: 5,
+					period_count: 1,
+					per_period: 1,
+					label: Some(label.clone()),
+					category: None,
+				},
+			]
+		);
+		assert_eq!(vesting_frozen(&BOB), 14);
+		assert_eq!(PalletBalances::free_balance(&BOB), 29);
+		System::set_block_number(20);
+		assert_ok!(Vesting::claim(RuntimeOrigin::signed(BOB)));
+		assert_eq!(vesting_frozen(&BOB), 0);
+		assert_ok!(Vesting::approve_schedule_transfer(RuntimeOrigin::signed(ALICE), BOB, 1, CHARLIE));
+
+		// Once everything has vested, a new payout starts a schedule of its own. Dropping the
+		// vested schedules also drops the approval, which would point at another schedule.
+		assert_ok!(Vesting::vested_transfer_merging(&ALICE, &BOB, 10, 5, 2, label.clone()));
+		assert_eq!(TransferApprovals::<Runtime>::get(&BOB), None);
+		assert_eq!(
+			Vesting::vesting_schedules(&BOB).to_vec(),
+			vec![VestingSchedule {
+				start: 20,
+				period: 5,
+				period_count: 2,
+				per_period: 5,
+				label: Some(label),
+				category: None,
+			}]
+		);
+		assert_eq!(vesting_frozen(&BOB), 10);
+	});
+}
+
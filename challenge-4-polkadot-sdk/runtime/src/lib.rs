@@ -35,7 +35,14 @@ use polkadot_sdk::{
 	},
 	*,
 };
-use polkadot_sdk::sp_runtime::{traits::{ConvertInto, IdentifyAccount, Verify}, MultiSignature};
+use polkadot_sdk::frame_support::traits::{
+	fungible::{NativeFromLeft, NativeOrWithId, UnionOf},
+	AsEnsureOriginWithArg, Contains, EitherOf, NeverEnsureOrigin, VariantCountOf,
+};
+use polkadot_sdk::sp_runtime::{
+	traits::{ConvertInto, IdentifyAccount, StaticLookup, Verify},
+	MultiSignature,
+};
 
 /// Alias to 512-bit hash when used in the context of a transaction signature on the chain.
 pub type Signature = MultiSignature;
@@ -44,6 +51,15 @@ pub type Signature = MultiSignature;
 /// to the public key of our transaction signing scheme.
 pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
 
+/// Expected time between blocks, in milliseconds.
+pub const MILLISECS_PER_BLOCK: u64 = 6000;
+
+// Time is measured by number of blocks.
+pub const MINUTES: interface::BlockNumber =
+	60_000 / (MILLISECS_PER_BLOCK as interface::BlockNumber);
+pub const HOURS: interface::BlockNumber = MINUTES * 60;
+pub const DAYS: interface::BlockNumber = HOURS * 24;
+
 
 /// Provides getters for genesis configuration presets.
 pub mod genesis_config_presets {
@@ -51,7 +67,7 @@ pub mod genesis_config_presets {
 	use crate::{
 		interface::{Balance, MinimumBalance},
 		sp_keyring::Sr25519Keyring,
-		BalancesConfig, RuntimeGenesisConfig, SudoConfig,
+		BalancesConfig, NativePoolsConfig, RuntimeGenesisConfig, SudoConfig,
 	};
 
 	use alloc::{vec, vec::Vec};
@@ -67,6 +83,9 @@ pub mod genesis_config_presets {
 					.collect::<Vec<_>>(),
 			},
 			sudo: SudoConfig { key: Some(Sr25519Keyring::Alice.to_account_id()) },
+			native_pools: NativePoolsConfig {
+				reward_team: vec![Sr25519Keyring::Alice.to_account_id()],
+			},
 		})
 	}
 
@@ -178,6 +197,14 @@ mod runtime {
 
 	#[runtime::pallet_index(6)]
 	pub type NativePools = pallet_native_pools::Pallet<Runtime>;
+
+	/// Provides fungible assets other than the native currency.
+	#[runtime::pallet_index(7)]
+	pub type Assets = pallet_assets::Pallet<Runtime>;
+
+	/// Provides non-fungible items, used for native pool positions.
+	#[runtime::pallet_index(8)]
+	pub type Nfts = pallet_nfts::Pallet<Runtime>;
 }
 
 parameter_types! {
@@ -192,12 +219,38 @@ impl frame_system::Config for Runtime {
 	// Use the account data from the balances pallet
 	type AccountData = pallet_balances::AccountData<<Runtime as pallet_balances::Config>::Balance>;
 	type AccountId = AccountId;
+	type BaseCallFilter = RuntimeCallFilter;
+}
+
+/// Blocks direct transfers of the native pools receipt token, which must be moved with
+/// `NativePools::transfer_receipt` so that the rewards of the deposit behind it are settled.
+pub struct RuntimeCallFilter;
+
+impl Contains<RuntimeCall> for RuntimeCallFilter {
+	fn contains(call: &RuntimeCall) -> bool {
+		let is_receipt = |id: &<Runtime as pallet_assets::Config>::AssetIdParameter| {
+			let id: <Runtime as pallet_assets::Config>::AssetId = id.clone().into();
+			id == NativePoolsReceiptAsset::get()
+		};
+		match call {
+			RuntimeCall::Assets(
+				pallet_assets::Call::transfer { id, .. } |
+				pallet_assets::Call::transfer_keep_alive { id, .. } |
+				pallet_assets::Call::approve_transfer { id, .. } |
+				pallet_assets::Call::transfer_approved { id, .. } |
+				pallet_assets::Call::transfer_all { id, .. },
+			) => !is_receipt(id),
+			_ => true,
+		}
+	}
 }
 
 // Implements the types required for the balances pallet.
 #[derive_impl(pallet_balances::config_preludes::TestDefaultConfig)]
 impl pallet_balances::Config for Runtime {
 	type AccountStore = System;
+	type FreezeIdentifier = RuntimeFreezeReason;
+	type MaxFreezes = VariantCountOf<RuntimeFreezeReason>;
 }
 
 // Implements the types required for the sudo pallet.
@@ -218,37 +271,229 @@ impl pallet_transaction_payment::Config for Runtime {
 	type LengthToFee = FixedFee<1, <Self as pallet_balances::Config>::Balance>;
 }
 
+// Implements the types required for the assets pallet.
+#[derive_impl(pallet_assets::config_preludes::TestDefaultConfig)]
+impl pallet_assets::Config for Runtime {
+	type Currency = Balances;
+	type CreateOrigin = AsEnsureOriginWithArg<EnsureSigned<AccountId>>;
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type Freezer = ();
+}
+
+parameter_types! {
+    /// Only the native pools pallet creates a collection, for its positions, and it keeps all
+    /// attributes to itself.
+    pub NftFeatures: pallet_nfts::PalletFeatures =
+        pallet_nfts::PalletFeatures::from_disabled(pallet_nfts::PalletFeature::Attributes.into());
+    pub const NftCollectionDeposit: interface::Balance = 0;
+    pub const NftItemDeposit: interface::Balance = 10;
+    pub const NftMetadataDepositBase: interface::Balance = 10;
+    pub const NftDepositPerByte: interface::Balance = 1;
+}
+
+// Implements the types required for the NFTs pallet.
+impl pallet_nfts::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type CollectionId = u32;
+	type ItemId = u32;
+	type Currency = Balances;
+	type CreateOrigin = AsEnsureOriginWithArg<NeverEnsureOrigin<AccountId>>;
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type Locker = ();
+	type CollectionDeposit = NftCollectionDeposit;
+	type ItemDeposit = NftItemDeposit;
+	type MetadataDepositBase = NftMetadataDepositBase;
+	type AttributeDepositBase = NftMetadataDepositBase;
+	type DepositPerByte = NftDepositPerByte;
+	type StringLimit = ConstU32<256>;
+	type KeyLimit = ConstU32<64>;
+	type ValueLimit = ConstU32<256>;
+	type ApprovalsLimit = ConstU32<20>;
+	type ItemAttributesApprovalsLimit = ConstU32<30>;
+	type MaxTips = ConstU32<10>;
+	type MaxDeadlineDuration = ConstU32<{ 12 * 30 * DAYS }>;
+	type MaxAttributesPerCall = ConstU32<10>;
+	type Features = NftFeatures;
+	type OffchainSignature = Signature;
+	type OffchainPublic = <Signature as Verify>::Signer;
+	type WeightInfo = ();
+	type BlockNumberProvider = System;
+}
+
 parameter_types! {
     pub const MaxVestingSchedules: u32 = 10;
     pub const MinVestedTransfer: u32 = 100;
+    pub const RequireVestingTransferApproval: bool = true;
+    pub const VestingPalletId: PalletId = PalletId(*b"py/vestn");
+    pub const EthereumClaimPrefix: &'static [u8] = b"Pay DOTs to the vesting account:";
 }
 
 // Implements the types required for the template pallet.
 impl pallet_vesting::Config for Runtime {
-
+	type RuntimeFreezeReason = RuntimeFreezeReason;
 	type Currency = Balances;
 	type BlockNumberToBalance = ConvertInto;
 	type MaxVestingSchedules = MaxVestingSchedules;
 	type MinVestedTransfer = MinVestedTransfer;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	type RequireTransferApproval = RequireVestingTransferApproval;
+	type AssetId = <Runtime as pallet_assets::Config>::AssetId;
+	type Assets = Assets;
+	type PalletId = VestingPalletId;
+	type EthereumClaimPrefix = EthereumClaimPrefix;
+	type MaxProofLength = ConstU32<32>;
+	type MaxUnlocksPerBlock = ConstU32<64>;
 }
 
 parameter_types! {
     pub const NativePoolsPalletId: PalletId = PalletId(*b"py/natpl");
+    pub VestingFreezeId: RuntimeFreezeReason = pallet_vesting::FreezeReason::Vesting.into();
+    pub NativePoolsTreasury: AccountId =
+        sp_runtime::traits::AccountIdConversion::into_account_truncating(&PalletId(*b"py/trsry"));
+    pub const NativePoolsReceiptAsset: u32 = 1_000_000;
+    pub const NativePoolsEntryDeposit: interface::Balance = 10;
+    pub const NativePoolsVaultVirtualShares: interface::Balance = 1_000;
+    pub NativePoolsRewardReserve: AccountId =
+        sp_runtime::traits::AccountIdConversion::into_account_truncating(&PalletId(*b"py/nprsv"));
+    pub const NativePoolUnbondingPeriod: interface::BlockNumber = 7 * DAYS;
+    pub NativePoolLockTerms: Vec<pallet_native_pools::LockTerm<interface::BlockNumber>> = alloc::vec![
+        pallet_native_pools::LockTerm { duration: 0, reward_multiplier: 100 },
+        pallet_native_pools::LockTerm { duration: 30 * DAYS, reward_multiplier: 110 },
+        pallet_native_pools::LockTerm { duration: 90 * DAYS, reward_multiplier: 130 },
+        pallet_native_pools::LockTerm { duration: 180 * DAYS, reward_multiplier: 160 },
+    ];
 }
 
 
 impl pallet_native_pools::Config for Runtime {
+	type RuntimeHoldReason = RuntimeHoldReason;
 	type Currency = Balances;
+	type VestingFreezeId = VestingFreezeId;
 	type PalletId = NativePoolsPalletId;
-	type RewardOrigin = EnsureSigned<AccountId>;
+	// Root deposits rewards from the reward reserve, reward team members from their accounts.
+	type RewardOrigin = EitherOf<
+		EnsureRootWithSuccess<AccountId, NativePoolsRewardReserve>,
+		pallet_native_pools::EnsureRewardTeam<Runtime>,
+	>;
+	type RewardVesting = VestPoolRewards;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	type LockTerms = NativePoolLockTerms;
+	type MaxBoostExpiriesPerBlock = ConstU32<64>;
+	type MaxBoostExpiryBlocks = ConstU32<1024>;
+	type UnbondingPeriod = NativePoolUnbondingPeriod;
+	type MaxUnlockingChunks = ConstU32<16>;
+	type TreasuryAccount = NativePoolsTreasury;
+	type AssetId = <Runtime as pallet_assets::Config>::AssetId;
+	type Assets = Assets;
+	type ReceiptAssetId = NativePoolsReceiptAsset;
+	type CollectionId = <Runtime as pallet_nfts::Config>::CollectionId;
+	type Nfts = Nfts;
+	type NftCollectionConfig = pallet_nfts::CollectionConfigFor<Runtime>;
+	type NftItemConfig = pallet_nfts::ItemConfig;
+	type MaxRewardAssets = ConstU32<8>;
+	type EntryDeposit = NativePoolsEntryDeposit;
+	type VaultVirtualShares = NativePoolsVaultVirtualShares;
+	type StakingToken = NativeOrWithId<<Runtime as pallet_assets::Config>::AssetId>;
+	type StakingAssets = UnionOf<
+		Balances,
+		Assets,
+		NativeFromLeft,
+		NativeOrWithId<<Runtime as pallet_assets::Config>::AssetId>,
+		AccountId,
+	>;
+	type MaxRewardSnapshots = ConstU32<64>;
+	type BlocksPerDay = ConstU32<DAYS>;
+	type RewardReserve = NativePoolsRewardReserve;
+	type KeeperAuthorityId = pallet_native_pools::crypto::KeeperAuthId;
+	type OnPoolChange = ();
+}
+
+impl frame_system::offchain::SigningTypes for Runtime {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<LocalCall> frame_system::offchain::CreateTransactionBase<LocalCall> for Runtime
+where
+	RuntimeCall: From<LocalCall>,
+{
+	type RuntimeCall = RuntimeCall;
+	type Extrinsic = ExtrinsicFor<Runtime>;
+}
 
+/// Signs the transactions of offchain workers, such as the native pools keeper.
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Runtime
+where
+	RuntimeCall: From<LocalCall>,
+{
+	fn create_signed_transaction<
+		C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>,
+	>(
+		call: RuntimeCall,
+		public: <Signature as Verify>::Signer,
+		account: AccountId,
+		nonce: interface::Nonce,
+	) -> Option<ExtrinsicFor<Runtime>> {
+		let tx_ext: TxExtension = (
+			frame_system::CheckNonZeroSender::<Runtime>::new(),
+			frame_system::CheckSpecVersion::<Runtime>::new(),
+			frame_system::CheckTxVersion::<Runtime>::new(),
+			frame_system::CheckGenesis::<Runtime>::new(),
+			frame_system::CheckEra::<Runtime>::from(sp_runtime::generic::Era::Immortal),
+			frame_system::CheckNonce::<Runtime>::from(nonce),
+			frame_system::CheckWeight::<Runtime>::new(),
+			pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+			frame_system::WeightReclaim::<Runtime>::new(),
+		);
+		let raw_payload = sp_runtime::generic::SignedPayload::new(call, tx_ext).ok()?;
+		let signature = raw_payload.using_encoded(|payload| C::sign(payload, public))?;
+		let (call, tx_ext, _) = raw_payload.deconstruct();
+		let address = <Runtime as frame_system::Config>::Lookup::unlookup(account);
+		Some(ExtrinsicFor::<Runtime>::new_signed(call, address, signature, tx_ext))
+	}
+}
+
+/// Vests native pool rewards in a single schedule per account, labelled `pool-rewards`.
+pub struct VestPoolRewards;
+
+impl
+	pallet_native_pools::RewardVesting<
+		AccountId,
+		interface::Balance,
+		interface::BlockNumber,
+	> for VestPoolRewards
+{
+	fn vest_reward(
+		source: &AccountId,
+		dest: &AccountId,
+		amount: interface::Balance,
+		period: interface::BlockNumber,
+		period_count: u32,
+	) -> sp_runtime::DispatchResult {
+		let label = pallet_vesting::ScheduleLabel::truncate_from(b"pool-rewards".to_vec());
+		Vesting::vested_transfer_merging(source, dest, amount, period, period_count, label)
+	}
 }
 
 type Block = frame::runtime::types_common::BlockOf<Runtime, TxExtension>;
 type Header = HeaderFor<Runtime>;
 
-type RuntimeExecutive =
-	Executive<Runtime, Block, frame_system::ChainContext<Runtime>, Runtime, AllPalletsWithSystem>;
+/// Migrations to apply on runtime upgrade.
+type Migrations = (
+	pallet_vesting::migrations::v1::MigrateV0ToV1<Runtime, Balances>,
+	pallet_vesting::migrations::v2::MigrateV1ToV2<Runtime>,
+	pallet_native_pools::migrations::v1::MigrateV0ToV1<Runtime>,
+	pallet_native_pools::migrations::v2::MigrateV1ToV2<Runtime>,
+);
+
+type RuntimeExecutive = Executive<
+	Runtime,
+	Block,
+	frame_system::ChainContext<Runtime>,
+	Runtime,
+	AllPalletsWithSystem,
+	Migrations,
+>;
 
 impl_runtime_apis! {
 	impl apis::Core<Block> for Runtime {
@@ -351,6 +596,48 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_native_pools::runtime_api::NativePoolsApi<
+		Block,
+		interface::AccountId,
+		interface::Balance,
+		<Runtime as pallet_assets::Config>::AssetId,
+	> for Runtime {
+		fn exit_fee(who: interface::AccountId, amount: interface::Balance) -> interface::Balance {
+			NativePools::exit_fee(&who, amount)
+		}
+
+		fn compounding_balance(who: interface::AccountId) -> interface::Balance {
+			NativePools::compounding_balance(&who)
+		}
+
+		fn pending_asset_rewards(
+			who: interface::AccountId,
+		) -> Vec<(<Runtime as pallet_assets::Config>::AssetId, interface::Balance)> {
+			NativePools::pending_asset_rewards(&who)
+		}
+
+		fn trailing_apr(days: u32) -> sp_runtime::FixedU128 {
+			NativePools::trailing_apr(days)
+		}
+	}
+
+	impl pallet_vesting::runtime_api::VestingApi<
+		Block,
+		interface::AccountId,
+		interface::Balance,
+		interface::BlockNumber,
+	> for Runtime {
+		fn vesting_details(
+			who: interface::AccountId,
+		) -> pallet_vesting::VestingDetails<interface::BlockNumber, interface::Balance> {
+			Vesting::vesting_details(&who)
+		}
+
+		fn category_totals(category: u32) -> pallet_vesting::CategoryTotals<interface::Balance> {
+			Vesting::category_totals(category)
+		}
+	}
+
 	impl apis::GenesisBuilder<Block> for Runtime {
 		fn build_state(config: Vec<u8>) -> sp_genesis_builder::Result {
 			build_state::<RuntimeGenesisConfig>(config)
@@ -380,6 +667,7 @@ pub mod interface {
 	pub type AccountId = <Runtime as frame_system::Config>::AccountId;
 	pub type Nonce = <Runtime as frame_system::Config>::Nonce;
 	pub type Hash = <Runtime as frame_system::Config>::Hash;
+	pub type BlockNumber = frame_system::pallet_prelude::BlockNumberFor<Runtime>;
 	pub type Balance = <Runtime as pallet_balances::Config>::Balance;
 	pub type MinimumBalance = <Runtime as pallet_balances::Config>::ExistentialDeposit;
 }